@@ -1,5 +1,4 @@
-use crate::GameColors;
-use image::Rgba;
+use crate::{Rgba, Theme};
 use strum_macros::Display;
 
 #[derive(Clone, Copy, Debug, Display, PartialEq, PartialOrd)]
@@ -11,11 +10,11 @@ pub enum AlarmLevel {
 }
 
 impl AlarmLevel {
-    pub fn rgba(&self) -> Rgba<u8> {
+    pub fn rgba(&self, theme: &Theme) -> Rgba<u8> {
         match self {
-            Self::NoMinotaurs | Self::NotChasing => Rgba([255; 4]),
-            Self::ChasingOtherHero => GameColors::MINOTAUR,
-            Self::ChasingHero => GameColors::CHASING_MINOTAUR,
+            Self::NoMinotaurs | Self::NotChasing => theme.text,
+            Self::ChasingOtherHero => theme.minotaur,
+            Self::ChasingHero => theme.chasing_minotaur,
         }
     }
 }
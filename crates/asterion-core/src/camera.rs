@@ -0,0 +1,158 @@
+use crate::Position;
+
+/// A window on the image of a room, following a hero. The camera only scrolls
+/// when the hero gets within a quarter of the window from its edges, and then
+/// eases towards its new origin over a few frames.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    maze_id: Option<usize>,
+    origin: (u32, u32),
+    size: (u32, u32),
+    cell_size: (u32, u32),
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            maze_id: None,
+            origin: (0, 0),
+            size: (0, 0),
+            cell_size: (1, 2),
+        }
+    }
+}
+
+impl Camera {
+    /// Size of the window in pixels. Characters of the terminal hold one
+    /// column of two pixels, see [`Camera::set_cell_size`].
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            size: (width, height),
+            ..Default::default()
+        }
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.size = (width, height);
+    }
+
+    /// Pixels held by a character of the terminal. The origin of the window
+    /// stays on a multiple of it, so that pixels keep the same characters.
+    pub fn set_cell_size(&mut self, width: u32, height: u32) {
+        self.cell_size = (width.max(1), height.max(1));
+    }
+
+    pub fn cell_size(&self) -> (u32, u32) {
+        self.cell_size
+    }
+
+    pub fn origin(&self) -> (u32, u32) {
+        self.origin
+    }
+
+    /// Size of the window, clamped to `bounds`.
+    pub fn view_size(&self, bounds: (u32, u32)) -> (u32, u32) {
+        (self.size.0.min(bounds.0), self.size.1.min(bounds.1))
+    }
+
+    /// Position relative to the window origin, if inside the window.
+    pub fn to_view(&self, position: Position, bounds: (u32, u32)) -> Option<(u32, u32)> {
+        let (width, height) = self.view_size(bounds);
+        let (x, y) = (position.0 as u32, position.1 as u32);
+        let (ox, oy) = self.origin;
+        if (ox..ox + width).contains(&x) && (oy..oy + height).contains(&y) {
+            Some((x - ox, y - oy))
+        } else {
+            None
+        }
+    }
+
+    fn axis_target(origin: u32, target: u32, view: u32, bound: u32) -> u32 {
+        let margin = view / 4;
+        let origin = if target < origin + margin {
+            target.saturating_sub(margin)
+        } else if target + margin >= origin + view {
+            target + margin + 1 - view
+        } else {
+            origin
+        };
+        origin.min(bound - view)
+    }
+
+    fn ease(from: u32, to: u32) -> u32 {
+        if from < to {
+            from + (to - from).div_ceil(2)
+        } else {
+            from - (from - to).div_ceil(2)
+        }
+    }
+
+    /// Moves the window towards `target` in room `maze_id`, whose image is
+    /// `bounds` pixels large. Jumps straight there when the room changes or
+    /// the target is out of sight, e.g. after a respawn.
+    pub fn follow(&mut self, maze_id: usize, target: Position, bounds: (u32, u32)) {
+        let (width, height) = self.view_size(bounds);
+        let (tx, ty) = (target.0 as u32, target.1 as u32);
+        let (cell_width, cell_height) = self.cell_size;
+        let align = |origin: u32, step: u32| origin - origin % step;
+        let x = align(
+            Self::axis_target(self.origin.0, tx, width, bounds.0),
+            cell_width,
+        );
+        let y = align(
+            Self::axis_target(self.origin.1, ty, height, bounds.1),
+            cell_height,
+        );
+
+        if self.maze_id != Some(maze_id) || self.to_view(target, bounds).is_none() {
+            self.maze_id = Some(maze_id);
+            self.origin = (x, y);
+        } else {
+            self.origin = (
+                align(Self::ease(self.origin.0, x), cell_width),
+                align(Self::ease(self.origin.1, y), cell_height),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Camera;
+
+    #[test]
+    fn test_follow() {
+        let bounds = (100, 60);
+        let mut camera = Camera::new(40, 20);
+
+        camera.follow(0, (50, 30), bounds);
+        assert_eq!(camera.origin(), (21, 16));
+        assert_eq!(camera.to_view((50, 30), bounds), Some((29, 14)));
+
+        // Moving within the window does not scroll it.
+        camera.follow(0, (45, 30), bounds);
+        assert_eq!(camera.origin(), (21, 16));
+
+        // Getting close to the edge eases the window along.
+        camera.follow(0, (58, 30), bounds);
+        assert_eq!(camera.origin(), (25, 16));
+        camera.follow(0, (58, 30), bounds);
+        assert_eq!(camera.origin(), (27, 16));
+
+        // The window never leaves the room.
+        camera.follow(1, (99, 59), bounds);
+        assert_eq!(camera.origin(), (60, 40));
+
+        // Rooms smaller than the window are shown whole.
+        camera.follow(2, (5, 5), (30, 10));
+        assert_eq!(camera.origin(), (0, 0));
+        assert_eq!(camera.view_size((30, 10)), (30, 10));
+
+        // Characters holding 2x3 pixels keep the window on their grid.
+        camera.set_cell_size(2, 3);
+        camera.follow(3, (50, 30), bounds);
+        assert_eq!(camera.origin(), (20, 15));
+        camera.follow(3, (58, 30), bounds);
+        assert_eq!(camera.origin(), (24, 15));
+    }
+}
@@ -0,0 +1,39 @@
+//! The time seen by the game. It is the real time, unless a simulated clock
+//! has been installed on the current thread, e.g. by `Env` to step the game
+//! on a discrete clock, or to pause a local game.
+
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+thread_local! {
+    static SIMULATED_NOW: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+pub(crate) fn now() -> Instant {
+    SIMULATED_NOW.get().unwrap_or_else(Instant::now)
+}
+
+pub(crate) fn elapsed(instant: Instant) -> Duration {
+    now().saturating_duration_since(instant)
+}
+
+/// Makes the game see `instant` as the current time on this thread, until
+/// the clock is dropped.
+pub struct SimulatedClock;
+
+impl SimulatedClock {
+    pub fn install(instant: Instant) -> Self {
+        SIMULATED_NOW.set(Some(instant));
+        Self
+    }
+
+    pub fn set(&self, instant: Instant) {
+        SIMULATED_NOW.set(Some(instant));
+    }
+}
+
+impl Drop for SimulatedClock {
+    fn drop(&mut self) {
+        SIMULATED_NOW.set(None);
+    }
+}
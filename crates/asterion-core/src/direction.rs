@@ -1,6 +1,7 @@
 use strum_macros::{Display, EnumIter};
 
 #[derive(Debug, Copy, Clone, Display, PartialEq, Eq, Hash, EnumIter)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction {
     North,
     East,
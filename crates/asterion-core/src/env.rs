@@ -0,0 +1,224 @@
+//! A gym-like environment to train agents on the game, without any I/O: a
+//! single hero plays a `Game` stepped on a discrete clock.
+
+use crate::{
+    clock::{self, SimulatedClock},
+    Game, GameCommand, GameConfig, GameEvent, Observation, PlayerId, MAX_MAZE_ID,
+};
+use anyhow::{anyhow, Result as AppResult};
+use rand::{RngExt, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::time::Instant;
+use uuid::Builder;
+
+/// Rewards for what happens during a step, summed up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RewardConfig {
+    /// For each room forward, and taken back for each room backward.
+    pub room_progress: f64,
+    pub power_up: f64,
+    pub death: f64,
+    pub victory: f64,
+    /// For each step survived.
+    pub survival: f64,
+    /// For each second spent in the labyrinth, usually negative.
+    pub time: f64,
+}
+
+impl Default for RewardConfig {
+    fn default() -> Self {
+        Self {
+            room_progress: 1.0,
+            power_up: 0.1,
+            death: -1.0,
+            victory: MAX_MAZE_ID as f64,
+            survival: 0.0,
+            time: -0.01,
+        }
+    }
+}
+
+/// The labyrinth for a single hero. Every `step` plays one command and
+/// advances the clock by `Game::update_time_step` ticks until the hero can
+/// move again. The episode is over when the hero dies or gets out.
+///
+/// Seeds fix the layout of the rooms and how minotaurs roam, so that the same
+/// commands play out the same episode.
+pub struct Env {
+    config: GameConfig,
+    rewards: RewardConfig,
+    game: Game,
+    hero_id: PlayerId,
+    now: Instant,
+    done: bool,
+}
+
+impl Env {
+    /// Ticks a single step can last at most, in case the hero cannot move.
+    const MAX_TICKS_PER_STEP: usize = 64;
+
+    pub fn new(config: GameConfig, rewards: RewardConfig) -> AppResult<Self> {
+        let now = Instant::now();
+        let (game, hero_id) = Self::build(&config, now)?;
+        Ok(Self {
+            config,
+            rewards,
+            game,
+            hero_id,
+            now,
+            done: false,
+        })
+    }
+
+    fn build(config: &GameConfig, now: Instant) -> AppResult<(Game, PlayerId)> {
+        let _clock = SimulatedClock::install(now);
+        let mut game = Game::with_config(config.clone())?;
+        let hero_id = config.seed.map_or_else(PlayerId::new_v4, |seed| {
+            Builder::from_random_bytes(ChaCha8Rng::seed_from_u64(seed).random()).into_uuid()
+        });
+        game.add_player(hero_id, "agent");
+        game.drain_events();
+        Ok((game, hero_id))
+    }
+
+    pub fn reset(&mut self, seed: u64) -> AppResult<Observation> {
+        self.config.seed = Some(seed);
+        (self.game, self.hero_id) = Self::build(&self.config, self.now)?;
+        self.done = false;
+        self.observe()
+    }
+
+    pub fn observe(&self) -> AppResult<Observation> {
+        let _clock = SimulatedClock::install(self.now);
+        self.game.observe(self.hero_id)
+    }
+
+    pub fn step(&mut self, command: &GameCommand) -> AppResult<(Observation, f64, bool)> {
+        if self.done {
+            return Err(anyhow!("The episode is over, reset the environment"));
+        }
+
+        let clock = SimulatedClock::install(self.now);
+        let start = self.now;
+        self.game.handle_command(command, self.hero_id);
+
+        let mut reward = 0.0;
+        for _ in 0..Self::MAX_TICKS_PER_STEP {
+            self.now += Game::update_time_step();
+            clock.set(self.now);
+            self.game.update();
+
+            for event in self.game.drain_events() {
+                reward += self.reward(&event);
+            }
+
+            let hero = self
+                .game
+                .get_hero(&self.hero_id)
+                .ok_or(anyhow!("The hero left the game"))?;
+            self.done = hero.is_dead() || hero.has_won().is_some();
+            if self.done || hero.can_move() {
+                break;
+            }
+        }
+
+        reward += self.rewards.time * clock::elapsed(start).as_secs_f64();
+        if !self.done {
+            reward += self.rewards.survival;
+        }
+
+        Ok((self.game.observe(self.hero_id)?, reward, self.done))
+    }
+
+    fn reward(&self, event: &GameEvent) -> f64 {
+        match event {
+            GameEvent::RoomEntered { from, maze_id, .. } => {
+                self.rewards.room_progress * (*maze_id as f64 - *from as f64)
+            }
+            GameEvent::PowerUpCollected { .. } => self.rewards.power_up,
+            GameEvent::HeroDied { .. } => self.rewards.death,
+            GameEvent::Victory { .. } => self.rewards.victory,
+            _ => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Env, RewardConfig};
+    use crate::{Cell, Direction, Game, GameCommand, GameConfig, IntoDirection};
+    use anyhow::Result as AppResult;
+    use std::time::Duration;
+
+    #[test]
+    fn test_env_step() -> AppResult<()> {
+        let rewards = RewardConfig {
+            room_progress: 0.0,
+            power_up: 0.0,
+            death: 0.0,
+            victory: 0.0,
+            survival: 0.0,
+            time: -1.0,
+        };
+        let mut env = Env::new(GameConfig::default(), rewards)?;
+        let mut observation = env.reset(7)?;
+        assert_eq!(observation.state, "WaitingToStart");
+        assert_eq!(observation.maze_id, 0);
+
+        let mut elapsed = Duration::ZERO;
+        for _ in 0..20 {
+            // Walk along the first open cell next to the hero.
+            let direction = [
+                Direction::East,
+                Direction::South,
+                Direction::North,
+                Direction::West,
+            ]
+            .into_iter()
+            .find(|direction| {
+                let next = observation.position.into_direction(direction);
+                observation.visible.contains(&(next, Cell::Floor))
+            })
+            .unwrap_or(Direction::East);
+
+            let (next, reward, done) = env.step(&GameCommand::Move { direction })?;
+            if done {
+                break;
+            }
+            // Only time is rewarded, one tick at least per step.
+            if observation.state == "InMaze" {
+                let step = next.elapsed - elapsed;
+                assert!(step >= Game::update_time_step());
+                assert!((reward + step.as_secs_f64()).abs() < 1e-9);
+            }
+            elapsed = next.elapsed;
+            observation = next;
+        }
+
+        // The game ran on the discrete clock only.
+        assert_eq!(observation.state, "InMaze");
+        assert!(elapsed > Duration::ZERO);
+        assert_eq!(
+            elapsed.as_millis() % Game::update_time_step().as_millis(),
+            0
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_env_reset_is_seeded() -> AppResult<()> {
+        let mut env = Env::new(GameConfig::default(), RewardConfig::default())?;
+        let mut other = Env::new(GameConfig::default(), RewardConfig::default())?;
+        // The hero gets the same id and starting position.
+        assert_eq!(env.reset(42)?, other.reset(42)?);
+        for maze_id in 0..3 {
+            assert_eq!(
+                env.game.get_maze(maze_id).walls(),
+                other.game.get_maze(maze_id).walls()
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+use crate::{PlayerId, PowerUp};
+use std::time::Duration;
+
+/// Something which happened in the game, collected by `Game` and drained
+/// once per tick with `Game::drain_events`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type")
+)]
+pub enum GameEvent {
+    HeroJoined {
+        hero_id: PlayerId,
+        name: String,
+    },
+    HeroLeft {
+        hero_id: PlayerId,
+        name: String,
+    },
+    HeroDied {
+        hero_id: PlayerId,
+        name: String,
+        maze_id: usize,
+        minotaur_id: PlayerId,
+        minotaur_name: String,
+    },
+    RoomEntered {
+        hero_id: PlayerId,
+        name: String,
+        from: usize,
+        maze_id: usize,
+    },
+    PowerUpCollected {
+        hero_id: PlayerId,
+        name: String,
+        maze_id: usize,
+        power_up: PowerUp,
+    },
+    CheckpointReached {
+        hero_id: PlayerId,
+        name: String,
+        maze_id: usize,
+    },
+    Victory {
+        hero_id: PlayerId,
+        name: String,
+        duration: Duration,
+    },
+    /// The hero took the top of the leaderboard, `maze_id` is `MAX_MAZE_ID`
+    /// if the record is a victory.
+    NewRecord {
+        hero_id: PlayerId,
+        name: String,
+        maze_id: usize,
+        duration: Duration,
+    },
+}
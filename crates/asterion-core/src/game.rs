@@ -1,36 +1,96 @@
 use crate::{
+    camera::Camera,
+    clock,
     entity::Entity,
-    hero::{GameCommand, HeroState},
+    hero::{GameCommand, HeroState, UiOptions},
     minotaur::Minotaur,
-    utils::{is_transparent, random_minotaur_name, to_player_name},
-    AlarmLevel, GameColors, Hero, IntoDirection, Maze, PlayerId,
+    tile::{Tile, TileMap},
+    time_trial::{GhostTarget, RunRecord},
+    utils::{is_transparent, random_minotaur_name, random_npc_name, to_player_name},
+    AlarmLevel, Cell, GameEvent, Grid, Hero, IntoDirection, Maze, Npc, NpcStrategy, Observation,
+    PlayerId, Position, Rgba, SeenHero, SeenMinotaur, Theme,
 };
 use anyhow::{anyhow, Result as AppResult};
-use image::{Rgba, RgbaImage};
 use itertools::Itertools;
+use rand::{RngExt, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use std::{
     collections::{HashMap, HashSet},
-    time::{Duration, Instant},
+    sync::Arc,
+    time::Duration,
 };
+use strum_macros::Display;
 
 pub const MAX_MAZE_ID: usize = 10;
 pub const POWER_UPS_PER_ROOM: usize = 3;
+pub const MAX_MINOTAURS_PER_ROOM: usize = 16;
+
+/// What happens to a hero caught by a minotaur.
+#[derive(Debug, Clone, Copy, Default, Display, PartialEq)]
+pub enum DeathPolicy {
+    /// Start over from the first room, losing all power ups.
+    #[default]
+    Restart,
+    /// Respawn at the entrance of the room until all lives are lost.
+    Lives { lives: usize },
+    /// Respawn at the last shrine visited, if any.
+    Checkpoints,
+    /// Respawn at the entrance of the room, losing the last power up.
+    LosePowerUp,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GameConfig {
+    /// Seed for the maze generation. Random mazes are built if `None`.
+    pub seed: Option<u64>,
+    /// Record completed runs so that heros can race against their ghosts.
+    pub time_trial: bool,
+    pub death_policy: DeathPolicy,
+    /// Width and height in cells of every room. Rooms get a random size,
+    /// growing with their depth, if `None`.
+    pub room_size: Option<(usize, usize)>,
+    /// Themes loaded from the config, cycled after the built-in ones.
+    pub themes: Vec<Theme>,
+}
+
+impl GameConfig {
+    pub fn time_trial(seed: u64) -> Self {
+        Self {
+            seed: Some(seed),
+            time_trial: true,
+            ..Default::default()
+        }
+    }
+}
 
+/// Clones share the layout and visibility caches of the rooms and every hero
+/// until it changes, so that a snapshot of the game can be taken every frame.
+#[derive(Clone)]
 pub struct Game {
+    config: GameConfig,
+    themes: Vec<Theme>,
     mazes: [Maze; MAX_MAZE_ID],
     taken_names: HashSet<String>,
-    heros: HashMap<PlayerId, Hero>,
+    heros: HashMap<PlayerId, Arc<Hero>>,
     hero_rooms: [Vec<PlayerId>; MAX_MAZE_ID],
-    top_heros_map: HashMap<PlayerId, (String, usize, Duration)>,
-    top_heros: Vec<(PlayerId, String, usize, Duration)>,
+    top_heros_map: HashMap<PlayerId, (String, usize, Duration, bool)>,
+    top_heros: Vec<(PlayerId, String, usize, Duration, bool)>,
     minotaurs: HashMap<PlayerId, Minotaur>,
     minotaur_rooms: [Vec<PlayerId>; MAX_MAZE_ID],
     top_minotaurs_map: HashMap<PlayerId, (String, usize, usize)>,
     top_minotaurs: Vec<(PlayerId, String, usize, usize)>,
+    personal_bests: HashMap<PlayerId, RunRecord>,
+    leader_run: Option<RunRecord>,
+    total_deaths: usize,
+    total_victories: usize,
+    events: Vec<GameEvent>,
+    npcs: HashMap<PlayerId, Npc>,
 }
 
 impl Game {
     const RESPAWN_INTERVAL: Duration = Duration::from_millis(1500);
+    /// Records of heros who left the game kept in the leaderboard.
+    const MAX_DEPARTED_RECORDS: usize = 32;
 
     fn should_update_hero_record(&self, hero_id: PlayerId) -> bool {
         let hero = if let Some(hero) = self.get_hero(&hero_id) {
@@ -39,7 +99,7 @@ impl Game {
             return false;
         };
 
-        let &(_, record_maze_id, record_timer) =
+        let &(_, record_maze_id, record_timer, _) =
             if let Some(record) = self.top_heros_map.get(&hero_id) {
                 record
             } else {
@@ -71,26 +131,47 @@ impl Game {
                 return;
             };
 
+            let deathless = hero.stats().deaths == 0;
             let record = if let Some(duration) = hero.has_won() {
-                (hero.name().to_string(), MAX_MAZE_ID, duration)
+                (hero.name().to_string(), MAX_MAZE_ID, duration, deathless)
             } else {
                 (
                     hero.name().to_string(),
                     hero.maze_id(),
                     hero.elapsed_duration_from_start(),
+                    deathless,
                 )
             };
+            let (name, maze_id, duration, _) = record.clone();
             self.top_heros_map.insert(hero_id, record);
             self.update_top_heros();
+
+            // Records only ever improve, so a hero on top now holds a new best.
+            // Starting in the first room is not worth announcing, and npcs
+            // have a leaderboard of their own.
+            let leader = self
+                .top_heros
+                .iter()
+                .find(|(id, ..)| !self.is_npc(id))
+                .map(|(id, ..)| *id);
+            if leader == Some(hero_id) && maze_id > 0 {
+                self.events.push(GameEvent::NewRecord {
+                    hero_id,
+                    name,
+                    maze_id,
+                    duration,
+                });
+            }
         }
     }
 
     fn update_top_heros(&mut self) {
+        let mut departed = 0;
         self.top_heros = self
             .top_heros_map
             .iter()
-            .map(|(&id, (name, record_maze_id, duration))| {
-                (id, name.clone(), *record_maze_id, *duration)
+            .map(|(&id, (name, record_maze_id, duration, deathless))| {
+                (id, name.clone(), *record_maze_id, *duration, *deathless)
             })
             .sorted_by(|a, b| {
                 if b.2 == a.2 {
@@ -99,7 +180,23 @@ impl Game {
                     b.2.cmp(&a.2)
                 }
             })
+            .filter(|(id, ..)| {
+                if self.heros.contains_key(id) {
+                    return true;
+                }
+                departed += 1;
+                departed <= Self::MAX_DEPARTED_RECORDS
+            })
             .collect_vec();
+
+        if self.top_heros.len() < self.top_heros_map.len() {
+            let kept = self
+                .top_heros
+                .iter()
+                .map(|(id, ..)| *id)
+                .collect::<HashSet<_>>();
+            self.top_heros_map.retain(|id, _| kept.contains(id));
+        }
     }
 
     fn update_top_minotaurs(&mut self) {
@@ -124,17 +221,37 @@ impl Game {
             .collect_vec();
     }
 
-    pub fn update_time_step() -> Duration {
-        Duration::from_millis(25)
-    }
-
-    pub fn draw_time_step() -> Duration {
-        Duration::from_millis(50)
+    /// Builds a room. Seeded rooms are the same every time they are built, so
+    /// that a room reset during a time trial is the one every hero runs.
+    fn build_maze(
+        maze_id: usize,
+        seed: Option<u64>,
+        room_size: Option<(usize, usize)>,
+    ) -> AppResult<Maze> {
+        let mut maze = Maze::new(maze_id);
+        if let Some((width, height)) = room_size {
+            maze = maze.width(width).height(height);
+        }
+        if let Some(seed) = seed {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            let room_seeds = (0..=maze_id).map(|_| rng.random()).collect_vec();
+            maze.random_seed(room_seeds[maze_id]).build()
+        } else {
+            maze.build()
+        }
     }
 
-    pub fn new() -> AppResult<Self> {
+    #[allow(clippy::type_complexity)]
+    fn build_mazes(
+        seed: Option<u64>,
+        room_size: Option<(usize, usize)>,
+    ) -> AppResult<(
+        [Maze; MAX_MAZE_ID],
+        HashMap<PlayerId, Minotaur>,
+        [Vec<PlayerId>; MAX_MAZE_ID],
+    )> {
         let mut mazes: [Maze; MAX_MAZE_ID] = (0..MAX_MAZE_ID)
-            .map(|maze_id| Maze::new(maze_id).build())
+            .map(|maze_id| Self::build_maze(maze_id, seed, room_size))
             .collect::<AppResult<Vec<Maze>>>()?
             .try_into()
             .expect("MAX_MAZE_ID mismatch");
@@ -153,7 +270,74 @@ impl Game {
             minotaur_rooms[maze.id()] = maze_minotaurs;
         }
 
+        Ok((mazes, minotaurs, minotaur_rooms))
+    }
+
+    fn record_run(&mut self, hero_id: PlayerId) {
+        // Ghosts are for racing other players.
+        if self.is_npc(&hero_id) {
+            return;
+        }
+
+        let hero = if let Some(hero) = self.get_hero(&hero_id) {
+            hero
+        } else {
+            return;
+        };
+
+        let duration = if let Some(duration) = hero.has_won() {
+            duration
+        } else {
+            return;
+        };
+
+        let run = RunRecord {
+            name: hero.name().to_string(),
+            duration,
+            splits: hero.stats().splits.clone(),
+            path: hero.path().clone(),
+        };
+
+        if self
+            .leader_run
+            .as_ref()
+            .map(|leader| leader.duration > duration)
+            .unwrap_or(true)
+        {
+            self.leader_run = Some(run.clone());
+        }
+
+        if self
+            .personal_bests
+            .get(&hero_id)
+            .map(|best| best.duration > duration)
+            .unwrap_or(true)
+        {
+            self.personal_bests.insert(hero_id, run);
+        }
+    }
+
+    pub fn update_time_step() -> Duration {
+        Duration::from_millis(25)
+    }
+
+    pub fn draw_time_step() -> Duration {
+        Duration::from_millis(50)
+    }
+
+    pub fn new() -> AppResult<Self> {
+        Self::with_config(GameConfig::default())
+    }
+
+    pub fn with_config(config: GameConfig) -> AppResult<Self> {
+        let (mazes, minotaurs, minotaur_rooms) = Self::build_mazes(config.seed, config.room_size)?;
+
         Ok(Self {
+            themes: Theme::built_in()
+                .into_iter()
+                .chain(config.themes.iter().cloned())
+                .collect(),
+            config,
             mazes,
             heros: HashMap::new(),
             hero_rooms: [const { Vec::new() }; MAX_MAZE_ID],
@@ -164,10 +348,157 @@ impl Game {
             minotaur_rooms,
             top_minotaurs_map: HashMap::new(),
             top_minotaurs: vec![],
+            personal_bests: HashMap::new(),
+            leader_run: None,
+            total_deaths: 0,
+            total_victories: 0,
+            events: vec![],
+            npcs: HashMap::new(),
         })
     }
 
-    pub fn top_heros(&self) -> &Vec<(PlayerId, String, usize, Duration)> {
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    /// The theme picked by `ui_options`, the default one if it refers to a
+    /// custom theme that is no longer in the config.
+    pub fn theme(&self, ui_options: UiOptions) -> &Theme {
+        self.themes
+            .get(Theme::index(ui_options))
+            .unwrap_or(&self.themes[0])
+    }
+
+    /// Rebuilds every maze from a new seed, e.g. when the daily time trial
+    /// rolls over. All heros are sent back to the first room and the
+    /// records of the previous mazes are discarded.
+    pub fn reseed(&mut self, seed: u64) -> AppResult<()> {
+        let (mazes, minotaurs, minotaur_rooms) =
+            Self::build_mazes(Some(seed), self.config.room_size)?;
+        self.config.seed = Some(seed);
+        self.mazes = mazes;
+        self.minotaurs = minotaurs;
+        self.minotaur_rooms = minotaur_rooms;
+        self.top_minotaurs_map.clear();
+        self.update_top_minotaurs();
+
+        self.hero_rooms = [const { Vec::new() }; MAX_MAZE_ID];
+        self.top_heros_map.clear();
+        self.personal_bests.clear();
+        self.leader_run = None;
+
+        let maze = &mut self.mazes[0];
+        for hero in self.heros.values_mut().map(Arc::make_mut) {
+            hero.reset(maze.hero_starting_position());
+            maze.increase_attempted();
+            let visible_positions = maze.get_and_cache_visible_positions(
+                hero.position(),
+                hero.direction(),
+                hero.view(),
+            );
+            hero.update_past_visible_positions(visible_positions);
+            self.hero_rooms[0].push(hero.id());
+            self.top_heros_map.insert(
+                hero.id(),
+                (
+                    hero.name().to_string(),
+                    0,
+                    hero.elapsed_duration_from_start(),
+                    true,
+                ),
+            );
+        }
+        self.update_top_heros();
+
+        Ok(())
+    }
+
+    /// Rebuilds a single room with as many minotaurs as before. The layout is
+    /// only new in unseeded games, seeded rooms being rebuilt as they were.
+    /// The heros in it are sent back to its entrance.
+    pub fn reset_room(&mut self, maze_id: usize) -> AppResult<()> {
+        if maze_id >= MAX_MAZE_ID {
+            return Err(anyhow!("No room {maze_id}"));
+        }
+
+        let mut maze = Self::build_maze(maze_id, self.config.seed, self.config.room_size)?;
+        maze.success_rate = self.mazes[maze_id].success_rate;
+        self.mazes[maze_id] = maze;
+
+        let number_of_minotaurs = self.minotaur_rooms[maze_id].len();
+        self.set_minotaurs_in_maze(maze_id, 0)?;
+        self.set_minotaurs_in_maze(maze_id, number_of_minotaurs)?;
+
+        let maze = &mut self.mazes[maze_id];
+        for hero in self.heros.values_mut().map(Arc::make_mut) {
+            hero.forget_maze(maze_id);
+            if let (Some((checkpoint_maze_id, _)), Some(shrine)) =
+                (hero.checkpoint(), maze.shrine_position())
+            {
+                if checkpoint_maze_id == maze_id {
+                    hero.set_checkpoint(maze_id, shrine);
+                }
+            }
+
+            if self.hero_rooms[maze_id].contains(&hero.id()) {
+                hero.set_position(maze.hero_starting_position());
+            }
+            // Heros who won are no longer in the room, but still see it.
+            if hero.maze_id() == maze_id {
+                let visible_positions = maze.get_and_cache_visible_positions(
+                    hero.position(),
+                    hero.direction(),
+                    hero.view(),
+                );
+                hero.update_past_visible_positions(visible_positions);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Spawns or removes minotaurs until the room has `count` of them.
+    pub fn set_minotaurs_in_maze(&mut self, maze_id: usize, count: usize) -> AppResult<()> {
+        if maze_id >= MAX_MAZE_ID {
+            return Err(anyhow!("No room {maze_id}"));
+        }
+        if count > MAX_MINOTAURS_PER_ROOM {
+            return Err(anyhow!(
+                "At most {MAX_MINOTAURS_PER_ROOM} minotaurs per room"
+            ));
+        }
+
+        let room = &mut self.minotaur_rooms[maze_id];
+        for minotaur_id in room.split_off(count.min(room.len())) {
+            self.minotaurs.remove(&minotaur_id);
+        }
+
+        while self.minotaur_rooms[maze_id].len() < count {
+            let index = self.minotaur_rooms[maze_id].len();
+            let name = format!("{}#{}{}", random_minotaur_name(), maze_id, index);
+            let minotaur = self.mazes[maze_id].spawn_minotaur(name);
+            self.minotaur_rooms[maze_id].push(minotaur.id());
+            self.minotaurs.insert(minotaur.id(), minotaur);
+        }
+
+        self.update_top_minotaurs();
+        Ok(())
+    }
+
+    /// The recorded run the hero is currently racing against, if any.
+    pub fn ghost_run(&self, hero: &Hero) -> Option<&RunRecord> {
+        if !self.config.time_trial {
+            return None;
+        }
+
+        match hero.ghost_target() {
+            GhostTarget::Off => None,
+            GhostTarget::PersonalBest => self.personal_bests.get(&hero.id()),
+            GhostTarget::Leader => self.leader_run.as_ref(),
+        }
+    }
+
+    pub fn top_heros(&self) -> &Vec<(PlayerId, String, usize, Duration, bool)> {
         &self.top_heros
     }
 
@@ -175,6 +506,20 @@ impl Game {
         &self.top_minotaurs
     }
 
+    pub fn heros_in_maze(&self, maze_id: usize) -> usize {
+        self.hero_rooms[maze_id].len()
+    }
+
+    /// Deaths since the game started.
+    pub fn total_deaths(&self) -> usize {
+        self.total_deaths
+    }
+
+    /// Victories since the game started.
+    pub fn total_victories(&self) -> usize {
+        self.total_victories
+    }
+
     pub fn minotaurs_in_maze(&self, maze_id: usize) -> usize {
         self.minotaur_rooms[maze_id].len()
     }
@@ -210,12 +555,7 @@ impl Game {
         (AlarmLevel::NoMinotaurs, usize::MAX)
     }
 
-    pub fn add_player(&mut self, player_id: PlayerId, name: &str) {
-        let rng = &mut rand::rng();
-        let mut player_name = to_player_name(rng, name);
-        while self.taken_names.contains(&player_name) {
-            player_name = to_player_name(rng, name);
-        }
+    fn insert_hero(&mut self, player_id: PlayerId, player_name: String) {
         self.taken_names.insert(player_name.clone());
 
         let maze = &mut self.mazes[0];
@@ -234,20 +574,114 @@ impl Game {
                 hero.name().to_string(),
                 0,
                 hero.elapsed_duration_from_start(),
+                true,
             ),
         );
 
+        self.events.push(GameEvent::HeroJoined {
+            hero_id: player_id,
+            name: hero.name().to_string(),
+        });
+        self.heros.insert(player_id, Arc::new(hero));
         self.update_top_heros();
+    }
 
-        self.heros.insert(player_id, hero);
+    pub fn add_player(&mut self, player_id: PlayerId, name: &str) {
+        let rng = &mut rand::rng();
+        let mut player_name = to_player_name(rng, name);
+        while self.taken_names.contains(&player_name) {
+            player_name = to_player_name(rng, name);
+        }
+        self.insert_hero(player_id, player_name);
     }
 
+    /// Adds a player with a reserved name, e.g. one tied to a public key.
+    /// Falls back to a random suffix if the name is already in use.
+    pub fn add_player_with_name(&mut self, player_id: PlayerId, name: &str) {
+        if self.taken_names.contains(name) {
+            self.add_player(player_id, name);
+        } else {
+            self.insert_hero(player_id, name.to_string());
+        }
+    }
+
+    pub fn set_hero_preferences(
+        &mut self,
+        player_id: &PlayerId,
+        ui_options: UiOptions,
+        ghost_target: GhostTarget,
+        announcements_muted: bool,
+    ) {
+        if let Some(hero) = self.hero_mut(player_id) {
+            hero.set_ui_options(ui_options);
+            hero.set_ghost_target(ghost_target);
+            hero.set_announcements_muted(announcements_muted);
+        }
+    }
+
+    /// The best room reached and the time of the hero's record.
+    pub fn hero_record(&self, player_id: &PlayerId) -> Option<(usize, Duration)> {
+        self.top_heros_map
+            .get(player_id)
+            .map(|&(_, maze_id, duration, _)| (maze_id, duration))
+    }
+
+    /// Removes the hero and every trace of it from the game indexes, freeing
+    /// its name. Its record stays on the leaderboard. An attempt still in
+    /// progress is not counted in the success rate of its room.
     pub fn remove_player(&mut self, player_id: &PlayerId) {
-        self.heros.remove(player_id);
+        let hero = if let Some(hero) = self.heros.remove(player_id) {
+            hero
+        } else {
+            return;
+        };
+
+        self.hero_rooms[hero.maze_id()].retain(|id| id != player_id);
+        if !hero.is_dead() && hero.has_won().is_none() {
+            self.mazes[hero.maze_id()].decrease_attempted();
+        }
+
+        self.taken_names.remove(hero.name());
+        self.personal_bests.remove(player_id);
+        self.npcs.remove(player_id);
+        // The record stays on the leaderboard, among the best of those who
+        // left.
+        self.update_top_heros();
+
+        self.events.push(GameEvent::HeroLeft {
+            hero_id: *player_id,
+            name: hero.name().to_string(),
+        });
+    }
+
+    /// The events which happened since the last call.
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn disconnect_player(&mut self, player_id: &PlayerId) {
+        if let Some(hero) = self.hero_mut(player_id) {
+            hero.set_disconnected(true);
+        }
+    }
+
+    pub fn reconnect_player(&mut self, player_id: &PlayerId) {
+        if let Some(hero) = self.hero_mut(player_id) {
+            hero.set_disconnected(false);
+        }
+    }
+
+    pub fn heros(&self) -> impl Iterator<Item = &Hero> {
+        self.heros.values().map(Arc::as_ref)
     }
 
     pub fn get_hero(&self, id: &PlayerId) -> Option<&Hero> {
-        self.heros.get(id)
+        self.heros.get(id).map(Arc::as_ref)
+    }
+
+    /// The hero, copied first if a snapshot of the game still shares it.
+    fn hero_mut(&mut self, id: &PlayerId) -> Option<&mut Hero> {
+        self.heros.get_mut(id).map(Arc::make_mut)
     }
 
     pub fn get_minotaur(&self, id: &PlayerId) -> Option<&Minotaur> {
@@ -262,19 +696,91 @@ impl Game {
         self.heros.len()
     }
 
+    /// Adds a hero played by the server, removed like any other player.
+    pub fn add_npc(&mut self, strategy: NpcStrategy) -> PlayerId {
+        let npc_id = PlayerId::new_v4();
+        let name = random_npc_name(|name| {
+            self.npcs
+                .keys()
+                .filter_map(|id| self.get_hero(id))
+                .any(|hero| hero.name().starts_with(name))
+        });
+        self.add_player(npc_id, &name);
+        self.npcs.insert(npc_id, Npc::new(strategy));
+        npc_id
+    }
+
+    pub fn is_npc(&self, id: &PlayerId) -> bool {
+        self.npcs.contains_key(id)
+    }
+
+    pub fn npcs(&self) -> impl Iterator<Item = (&PlayerId, &Npc)> {
+        self.npcs.iter()
+    }
+
+    fn update_npcs(&mut self) {
+        let npc_ids = self.npcs.keys().copied().collect_vec();
+        for npc_id in npc_ids {
+            if !self.get_hero(&npc_id).is_some_and(|hero| hero.can_move()) {
+                continue;
+            }
+
+            let command = self.observe(npc_id).ok().and_then(|observation| {
+                self.npcs
+                    .get_mut(&npc_id)
+                    .and_then(|npc| npc.next_command(&observation))
+            });
+            if let Some(command) = command {
+                self.handle_command(&command, npc_id);
+            }
+        }
+    }
+
     pub fn update(&mut self) {
-        // Update heros
+        self.update_npcs();
+
+        // Update heros, leaving alone those which do not change.
         for hero in self.heros.values_mut() {
             match hero.state {
                 HeroState::WaitingToStart | HeroState::InMaze { .. } => {}
                 HeroState::Dead { instant, .. } => {
-                    if instant.elapsed() > Self::RESPAWN_INTERVAL {
+                    if clock::elapsed(instant) > Self::RESPAWN_INTERVAL {
+                        let hero = Arc::make_mut(hero);
+                        let respawn = match self.config.death_policy {
+                            DeathPolicy::Restart => None,
+                            DeathPolicy::Lives { lives } => {
+                                (hero.stats().deaths < lives).then(|| {
+                                    (
+                                        hero.maze_id(),
+                                        self.mazes[hero.maze_id()].hero_starting_position(),
+                                    )
+                                })
+                            }
+                            DeathPolicy::Checkpoints => hero.checkpoint(),
+                            DeathPolicy::LosePowerUp => {
+                                hero.lose_last_power_up();
+                                Some((
+                                    hero.maze_id(),
+                                    self.mazes[hero.maze_id()].hero_starting_position(),
+                                ))
+                            }
+                        };
+
                         // Move hero between rooms
                         self.hero_rooms[hero.maze_id()].retain(|id| *id != hero.id());
-                        self.hero_rooms[0].push(hero.id());
 
-                        let maze = &mut self.mazes[0];
-                        hero.reset(maze.hero_starting_position());
+                        let maze = if let Some((maze_id, position)) = respawn {
+                            self.hero_rooms[maze_id].push(hero.id());
+                            hero.respawn(maze_id, position);
+                            &mut self.mazes[maze_id]
+                        } else {
+                            self.hero_rooms[0].push(hero.id());
+                            let maze = &mut self.mazes[0];
+                            hero.reset(maze.hero_starting_position());
+                            maze
+                        };
+                        maze.increase_attempted();
+
                         let visible_positions = maze.get_and_cache_visible_positions(
                             hero.position(),
                             hero.direction(),
@@ -285,13 +791,15 @@ impl Game {
                 }
 
                 HeroState::Victory { instant, .. } => {
-                    if instant.elapsed() > Self::RESPAWN_INTERVAL {
+                    if clock::elapsed(instant) > Self::RESPAWN_INTERVAL {
+                        let hero = Arc::make_mut(hero);
                         // Move hero between rooms
                         self.hero_rooms[hero.maze_id()].retain(|id| *id != hero.id());
                         self.hero_rooms[0].push(hero.id());
 
                         let maze = &mut self.mazes[0];
                         hero.reset(maze.hero_starting_position());
+                        maze.increase_attempted();
                         let visible_positions = maze.get_and_cache_visible_positions(
                             hero.position(),
                             hero.direction(),
@@ -306,7 +814,12 @@ impl Game {
         // Update minotaurs
         let mut should_update_top_minotaurs = false;
 
-        for minotaur in self.minotaurs.values_mut() {
+        // In the order they were spawned, so that seeded games play out the
+        // same way every time.
+        for minotaur_id in self.minotaur_rooms.iter().flatten() {
+            let Some(minotaur) = self.minotaurs.get_mut(minotaur_id) else {
+                continue;
+            };
             let maze_id = minotaur.maze_id();
             let maze = &mut self.mazes[maze_id];
 
@@ -319,8 +832,10 @@ impl Game {
             let visible_heros = self
                 .heros
                 .values()
+                .map(Arc::as_ref)
                 .filter(|hero| {
                     !hero.is_dead()
+                        && !hero.is_disconnected()
                         && hero.maze_id() == maze_id
                         && visible_positions.contains(&hero.position())
                 })
@@ -335,6 +850,7 @@ impl Game {
                     hero.maze_id() == maze_id
                         && hero.position() == minotaur.position()
                         && !hero.is_dead()
+                        && !hero.is_disconnected()
                 })
                 .map(|hero| hero.id())
                 .collect_vec();
@@ -342,14 +858,37 @@ impl Game {
             for hero_id in catched_heros.iter() {
                 if let Some(hero) = self.heros.get_mut(hero_id) {
                     if let HeroState::InMaze { instant } = hero.state {
+                        let hero = Arc::make_mut(hero);
                         hero.state = HeroState::Dead {
-                            duration: instant.elapsed(),
-                            instant: Instant::now(),
-                        }
+                            duration: clock::elapsed(instant),
+                            instant: clock::now(),
+                        };
+                        hero.record_death();
+                        self.total_deaths += 1;
+                        self.events.push(GameEvent::HeroDied {
+                            hero_id: hero.id(),
+                            name: hero.name().to_string(),
+                            maze_id,
+                            minotaur_id: minotaur.id(),
+                            minotaur_name: minotaur.name().to_string(),
+                        });
                     }
                 }
             }
 
+            for hero in self
+                .heros
+                .values_mut()
+                .filter(|hero| hero.maze_id() == maze_id && !hero.is_dead())
+            {
+                let is_chasing = minotaur.is_chasing(hero.id());
+                let is_close =
+                    is_chasing && minotaur.position().distance_squared(hero.position()) <= 2;
+                if hero.tracks_near_miss(minotaur.id(), is_chasing, is_close) {
+                    Arc::make_mut(hero).track_near_miss(minotaur.id(), is_chasing, is_close);
+                }
+            }
+
             minotaur.kills += catched_heros.len();
             self.top_minotaurs_map.insert(
                 minotaur.id(),
@@ -367,147 +906,304 @@ impl Game {
         }
     }
 
-    pub fn image_char_overrides(
-        &self,
-        player_id: PlayerId,
-        image: &RgbaImage,
-    ) -> AppResult<HashMap<(u32, u32), char>> {
+    /// What the hero sees of their room, limited to the camera window.
+    pub fn tiles(&self, player_id: PlayerId, camera: &Camera) -> AppResult<TileMap> {
         let hero = if let Some(hero) = self.get_hero(&player_id) {
             hero
         } else {
-            return Err(anyhow!("Missing hero {player_id}"));
+            return Err(anyhow!("No hero with id {player_id}"));
         };
 
-        let maze = &self.mazes[hero.maze_id()];
+        let maze_id = hero.maze_id();
+        let maze = &self.mazes[maze_id];
+        let bounds = maze.dimensions();
+        let (width, height) = camera.view_size(bounds);
+        let mut tiles = TileMap::new(maze_id, width, height);
+        let put = |tiles: &mut TileMap, position: Position, tile: Tile| {
+            if let Some((x, y)) = camera.to_view(position, bounds) {
+                tiles.set(x, y, tile);
+            }
+        };
 
-        // Override empty positions.
         let visible_positions =
             maze.get_cached_visible_positions(hero.position(), hero.direction(), hero.view());
-        let mut override_positions = visible_positions
-            .iter()
-            .filter(|(x, y)| {
-                is_transparent(
-                    image.get_pixel(*x as u32, *y as u32),
-                    &Maze::background_color(),
-                )
-            })
-            .map(|&(x, y)| ((x as u32, y as u32), '·'))
-            .collect::<HashMap<(u32, u32), char>>();
 
-        for &(x, y) in maze.entrance_positions().iter() {
-            if !visible_positions.contains(&(x, y)) {
+        for (&(dx, dy), instant) in hero.past_visible_positions().iter() {
+            if camera.to_view((dx, dy), bounds).is_none() {
                 continue;
             }
 
-            if maze.id() > 0 {
-                for (idx, c) in (maze.id() + 1 - 1).to_string().chars().enumerate() {
-                    override_positions.insert((x as u32 + idx as u32 + 1, y as u32), c);
-                }
-                override_positions.insert((x as u32, y as u32), '←');
+            // Remembered walls fade with the time passed, positions in view
+            // are brighter the closer they are to the hero.
+            let is_valid = maze.is_valid_position((dx, dy));
+            let base_visibility = if is_valid { 0 } else { 125 };
+            let mut visibility = if clock::elapsed(*instant) < hero.past_visibility_duration() {
+                base_visibility
+                    - (base_visibility as f64 * clock::elapsed(*instant).as_millis() as f64
+                        / hero.past_visibility_duration().as_millis() as f64)
+                        as u8
+            } else {
+                0
+            };
+
+            let in_view = visible_positions.contains(&(dx, dy));
+            if in_view {
+                let distance = hero.position().distance((dx, dy));
+                visibility += ((255.0 - visibility as f64)
+                    * (1.0 - distance / hero.view().radius() as f64))
+                    as u8;
             }
+
+            let tile = match (is_valid, in_view) {
+                (false, _) => Tile::Wall { visibility },
+                (true, true) => Tile::Floor { visibility },
+                (true, false) => Tile::FoggedFloor,
+            };
+            put(&mut tiles, (dx, dy), tile);
         }
 
-        for &(x, y) in maze.exit_positions().iter() {
-            if !visible_positions.contains(&(x, y)) {
-                continue;
+        let visibility = |tiles: &TileMap, position: Position| match camera
+            .to_view(position, bounds)
+            .and_then(|(x, y)| tiles.get(x, y))
+        {
+            Some(Tile::Wall { visibility } | Tile::Floor { visibility }) => visibility,
+            _ => 0,
+        };
+
+        for &position in maze.entrance_positions().iter() {
+            if maze_id > 0 && visible_positions.contains(&position) {
+                let visibility = visibility(&tiles, position);
+                put(&mut tiles, position, Tile::Entrance { visibility });
             }
+        }
 
-            for (idx, c) in (maze.id() + 1 + 1).to_string().chars().rev().enumerate() {
-                override_positions.insert((x as u32 - idx as u32 - 1, y as u32), c);
+        for &position in maze.exit_positions().iter() {
+            if visible_positions.contains(&position) {
+                let visibility = visibility(&tiles, position);
+                put(&mut tiles, position, Tile::Exit { visibility });
             }
-            override_positions.insert((x as u32, y as u32), '→');
         }
 
-        Ok(override_positions)
-    }
+        for &(x, y) in maze.power_up_positions.iter() {
+            if !hero.power_up_collected_at(maze_id, (x, y)) && visible_positions.contains(&(x, y)) {
+                put(&mut tiles, (x, y), Tile::PowerUp);
+            }
+        }
 
-    pub fn draw(&self, player_id: PlayerId) -> AppResult<RgbaImage> {
-        if let Some(hero) = self.heros.get(&player_id) {
-            let (x, y) = hero.position();
-            let maze_id = hero.maze_id();
-            let maze = &self.mazes[maze_id];
+        if self.config.death_policy == DeathPolicy::Checkpoints {
+            if let Some(position) = maze.shrine_position() {
+                if visible_positions.contains(&position) {
+                    put(&mut tiles, position, Tile::Shrine);
+                }
+            }
+        }
 
-            let maze_image = maze.image();
+        if let Some(ghost) = self
+            .ghost_run(hero)
+            .and_then(|run| run.ghost_at(hero.elapsed_duration_from_start()))
+        {
+            if ghost.maze_id == maze_id {
+                put(&mut tiles, ghost.position, Tile::Ghost);
+            }
+        }
 
-            // The base player image is black.
-            let mut player_image =
-                RgbaImage::from_pixel(maze_image.width(), maze_image.height(), Rgba([0; 4]));
+        for (p_id, any_hero) in self.heros.iter() {
+            if *p_id != player_id
+                && any_hero.maze_id() == maze_id
+                && !any_hero.is_disconnected()
+                && visible_positions.contains(&any_hero.position())
+            {
+                put(&mut tiles, any_hero.position(), Tile::OtherHero);
+            }
+        }
 
-            let visible_positions =
-                maze.get_cached_visible_positions(hero.position(), hero.direction(), hero.view());
+        for minotaur_id in self.minotaur_rooms[maze_id].iter() {
+            if let Some(minotaur) = self.get_minotaur(minotaur_id) {
+                if visible_positions.contains(&minotaur.position()) {
+                    let tile = if minotaur.is_chasing(hero.id()) {
+                        Tile::ChasingMinotaur
+                    } else {
+                        Tile::Minotaur
+                    };
+                    put(&mut tiles, minotaur.position(), tile);
+                }
+            }
+        }
 
-            for (&(dx, dy), instant) in hero.past_visible_positions().iter() {
-                // Each position in the past_visible_positions is copied from the maze_image, with alpha channel depending on the time passed.
-                let base_color = maze_image.get_pixel(dx as u32, dy as u32);
+        put(&mut tiles, hero.position(), Tile::Hero);
 
-                let is_valid = maze.is_valid_position((dx, dy));
+        Ok(tiles)
+    }
 
-                let base_alpha = if is_valid { 0 } else { 125 };
-                let mut alpha = if instant.elapsed() < hero.past_visibility_duration() {
-                    base_alpha
-                        - (base_alpha as f64 * instant.elapsed().as_millis() as f64
-                            / hero.past_visibility_duration().as_millis() as f64)
-                            as u8
-                } else {
-                    0
-                };
-
-                if visible_positions.contains(&(dx, dy)) {
-                    // Each position in the visible_positions is copied from the maze_image, with alpha channel depending on the distance from the hero.
-                    let distance = hero.position().distance((dx, dy));
-                    alpha += ((255.0 - alpha as f64)
-                        * (1.0 - distance / hero.view().radius() as f64))
-                        as u8;
-                }
+    /// Draws the parts of the room the hero remembers, scaled down to fit in
+    /// `width`x`height` pixels, with the entrance and exit once discovered.
+    pub fn draw_minimap(
+        &self,
+        player_id: PlayerId,
+        width: u32,
+        height: u32,
+    ) -> AppResult<Grid<Rgba<u8>>> {
+        let hero = if let Some(hero) = self.heros.get(&player_id) {
+            hero
+        } else {
+            return Err(anyhow!("No hero with id {player_id}"));
+        };
 
-                let pixel = Rgba([base_color[0], base_color[1], base_color[2], alpha]);
-                player_image.put_pixel(dx as u32, dy as u32, pixel);
-            }
+        let maze = &self.mazes[hero.maze_id()];
+        let theme = self.theme(hero.ui_options());
+        let walls = Maze::color(maze.id(), theme);
+        let (maze_width, maze_height) = maze.dimensions();
+        let scale = maze_width
+            .div_ceil(width.max(1))
+            .max(maze_height.div_ceil(height.max(1)))
+            .max(1);
+        let mut minimap = Grid::new(
+            maze_width.div_ceil(scale),
+            maze_height.div_ceil(scale),
+            Maze::background_color(),
+        );
 
-            // Add powerup position
-            for &(x, y) in maze.power_up_positions.iter() {
-                if !hero.power_up_collected_at(maze_id, (x, y))
-                    && visible_positions.contains(&(x, y))
-                {
-                    player_image.put_pixel(x as u32, y as u32, GameColors::POWER_UP);
-                }
+        let explored = hero
+            .past_visible_positions()
+            .iter()
+            .filter(|(_, &instant)| clock::elapsed(instant) < hero.minimap_duration())
+            .map(|(&position, _)| position)
+            .collect::<HashSet<Position>>();
+
+        // Explored passages win over the walls sharing the same pixel.
+        for &(x, y) in explored.iter() {
+            let (mx, my) = (x as u32 / scale, y as u32 / scale);
+            if maze.is_valid_position((x, y)) {
+                minimap.set(mx, my, theme.explored);
+            } else if minimap
+                .get(mx, my)
+                .is_some_and(|pixel| is_transparent(pixel, &Maze::background_color()))
+            {
+                minimap.set(mx, my, walls);
             }
+        }
 
-            // Add other heros position
-            for (p_id, any_hero) in self.heros.iter() {
-                if *p_id != player_id && any_hero.maze_id() == hero.maze_id() {
-                    let (ax, ay) = any_hero.position();
-                    if visible_positions.contains(&(ax, ay)) {
-                        player_image.put_pixel(ax as u32, ay as u32, GameColors::OTHER_HERO);
-                    }
+        for (positions, color) in [
+            (maze.entrance_positions(), theme.entrance),
+            (maze.exit_positions(), theme.exit),
+        ] {
+            if positions.iter().any(|position| explored.contains(position)) {
+                for &(x, y) in positions.iter() {
+                    minimap.set(x as u32 / scale, y as u32 / scale, color);
                 }
             }
+        }
 
-            // Add minotaurs position
-            let maze_minotaurs = &self.minotaur_rooms[hero.maze_id()];
-            for minotaur_id in maze_minotaurs.iter() {
-                if let Some(minotaur) = self.get_minotaur(minotaur_id) {
-                    let (mx, my) = minotaur.position();
-                    if visible_positions.contains(&(mx, my)) {
-                        if minotaur.is_chasing(hero.id()) {
-                            player_image.put_pixel(
-                                mx as u32,
-                                my as u32,
-                                GameColors::CHASING_MINOTAUR,
-                            );
-                        } else {
-                            player_image.put_pixel(mx as u32, my as u32, GameColors::MINOTAUR);
-                        }
-                    }
-                }
+        let (x, y) = hero.position();
+        minimap.set(x as u32 / scale, y as u32 / scale, theme.hero);
+
+        Ok(minimap)
+    }
+
+    /// What the hero can see, with the same rules used to draw it.
+    pub fn observe(&self, player_id: PlayerId) -> AppResult<Observation> {
+        let hero = if let Some(hero) = self.heros.get(&player_id) {
+            hero
+        } else {
+            return Err(anyhow!("No hero with id {player_id}"));
+        };
+
+        let maze_id = hero.maze_id();
+        let maze = &self.mazes[maze_id];
+        let visible_positions =
+            maze.get_cached_visible_positions(hero.position(), hero.direction(), hero.view());
+
+        let cell = |position: Position| {
+            if maze.is_entrance_position(position) {
+                Cell::Entrance
+            } else if maze.is_exit_position(position) {
+                Cell::Exit
+            } else if maze.is_valid_position(position) {
+                Cell::Floor
+            } else {
+                Cell::Wall
             }
+        };
 
-            // Add hero position
-            player_image.put_pixel(x as u32, y as u32, GameColors::HERO);
+        let visible = visible_positions
+            .iter()
+            .sorted()
+            .map(|&position| (position, cell(position)))
+            .collect_vec();
 
-            return Ok(player_image);
-        }
-        Err(anyhow!("No hero with id {player_id}"))
+        let remembered = hero
+            .past_visible_positions()
+            .iter()
+            .filter(|&(position, &instant)| {
+                !visible_positions.contains(position)
+                    && clock::elapsed(instant) < hero.past_visibility_duration()
+            })
+            .map(|(&position, _)| position)
+            .sorted()
+            .map(|position| (position, cell(position)))
+            .collect_vec();
+
+        let minotaurs = self.minotaur_rooms[maze_id]
+            .iter()
+            .filter_map(|id| self.get_minotaur(id))
+            .filter(|minotaur| visible_positions.contains(&minotaur.position()))
+            .map(|minotaur| SeenMinotaur {
+                id: minotaur.id(),
+                position: minotaur.position(),
+                direction: minotaur.direction(),
+                chasing: minotaur.is_chasing(player_id),
+            })
+            .collect_vec();
+
+        let heros = self.hero_rooms[maze_id]
+            .iter()
+            .filter(|&&id| id != player_id)
+            .filter_map(|id| self.get_hero(id))
+            .filter(|other| {
+                !other.is_disconnected() && visible_positions.contains(&other.position())
+            })
+            .map(|other| SeenHero {
+                id: other.id(),
+                name: other.name().to_string(),
+                position: other.position(),
+            })
+            .collect_vec();
+
+        let power_ups = maze
+            .power_up_positions
+            .iter()
+            .filter(|&&position| {
+                !hero.power_up_collected_at(maze_id, position)
+                    && visible_positions.contains(&position)
+            })
+            .copied()
+            .collect_vec();
+
+        let shrine = maze.shrine_position().filter(|position| {
+            self.config.death_policy == DeathPolicy::Checkpoints
+                && visible_positions.contains(position)
+        });
+
+        Ok(Observation {
+            hero_id: player_id,
+            state: hero.state.to_string(),
+            maze_id,
+            position: hero.position(),
+            direction: hero.direction(),
+            can_move: hero.can_move(),
+            vision: hero.vision(),
+            speed: hero.speed(),
+            memory: hero.memory(),
+            elapsed: hero.elapsed_duration_from_start(),
+            deaths: hero.stats().deaths,
+            victories: hero.victories(),
+            visible,
+            remembered,
+            minotaurs,
+            heros,
+            power_ups,
+            shrine,
+        })
     }
 
     pub fn handle_command(&mut self, command: &GameCommand, hero_id: PlayerId) {
@@ -517,10 +1213,19 @@ impl Game {
             return;
         };
 
+        if hero.is_disconnected() {
+            return;
+        }
+
+        let hero = Arc::make_mut(hero);
         if hero.state == HeroState::WaitingToStart {
-            hero.state = HeroState::InMaze {
-                instant: Instant::now(),
-            }
+            hero.start(
+                self.personal_bests
+                    .get(&hero_id)
+                    .map(|best| best.splits.clone())
+                    .unwrap_or_default(),
+                self.config.time_trial,
+            );
         }
 
         match hero.state {
@@ -566,14 +1271,34 @@ impl Game {
                         }
 
                         hero.set_position((new_x, new_y));
+                        hero.increase_steps();
                         for &position in self.mazes[maze_id].power_up_positions.iter() {
                             if position == hero.position()
                                 && !hero.power_up_collected_at(hero.maze_id(), hero.position())
                             {
-                                hero.apply_random_power_up_at_position(hero.position());
+                                let power_up =
+                                    hero.apply_random_power_up_at_position(hero.position());
+                                self.events.push(GameEvent::PowerUpCollected {
+                                    hero_id,
+                                    name: hero.name().to_string(),
+                                    maze_id,
+                                    power_up,
+                                });
                             }
                         }
 
+                        if self.config.death_policy == DeathPolicy::Checkpoints
+                            && self.mazes[maze_id].shrine_position() == Some(hero.position())
+                            && hero.checkpoint() != Some((maze_id, hero.position()))
+                        {
+                            hero.set_checkpoint(maze_id, hero.position());
+                            self.events.push(GameEvent::CheckpointReached {
+                                hero_id,
+                                name: hero.name().to_string(),
+                                maze_id,
+                            });
+                        }
+
                         // Transition between rooms
                         if self.mazes[maze_id].is_entrance_position(hero.position()) && maze_id > 0
                         {
@@ -581,6 +1306,12 @@ impl Game {
                             self.mazes[maze_id].decrease_attempted();
                             self.mazes[to].decrease_passed();
                             hero.set_maze_id(to);
+                            self.events.push(GameEvent::RoomEntered {
+                                hero_id,
+                                name: hero.name().to_string(),
+                                from: maze_id,
+                                maze_id: to,
+                            });
 
                             // Move hero between rooms
                             self.hero_rooms[maze_id].retain(|id| *id != hero.id());
@@ -602,17 +1333,32 @@ impl Game {
                         } else if self.mazes[maze_id].is_exit_position(hero.position()) {
                             let to = maze_id + 1;
                             self.mazes[maze_id].increase_passed();
+                            hero.record_split(maze_id, clock::elapsed(instant));
 
                             // Move hero between rooms
                             self.hero_rooms[maze_id].retain(|id| *id != hero.id());
 
                             if to == MAX_MAZE_ID {
+                                let duration = clock::elapsed(instant);
                                 hero.state = HeroState::Victory {
-                                    duration: instant.elapsed(),
-                                    instant: Instant::now(),
+                                    duration,
+                                    instant: clock::now(),
                                 };
+                                hero.record_victory();
+                                self.total_victories += 1;
+                                self.events.push(GameEvent::Victory {
+                                    hero_id,
+                                    name: hero.name().to_string(),
+                                    duration,
+                                });
                             } else {
                                 hero.set_maze_id(to);
+                                self.events.push(GameEvent::RoomEntered {
+                                    hero_id,
+                                    name: hero.name().to_string(),
+                                    from: maze_id,
+                                    maze_id: to,
+                                });
                                 self.hero_rooms[to].push(hero.id());
                                 self.mazes[to].increase_attempted();
 
@@ -677,22 +1423,482 @@ impl Game {
                         );
                     }
 
-                    GameCommand::CycleUiOptions => hero.cycle_ui_options(),
+                    GameCommand::CycleUiOptions => hero.cycle_ui_options(self.config.themes.len()),
+
+                    GameCommand::CycleGhostTarget => hero.cycle_ghost_target(),
+
+                    GameCommand::ToggleAnnouncements => hero.toggle_announcements(),
                 }
             }
             _ => {}
         }
 
         self.update_hero_record(hero_id);
+        self.record_run(hero_id);
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Game, MAX_MAZE_ID};
-    use crate::{game::utils::to_player_name, AppResult, PlayerId};
-    use rand::RngExt;
-    use std::time::Duration;
+    use super::{DeathPolicy, Game, GameConfig, MAX_MAZE_ID, MAX_MINOTAURS_PER_ROOM};
+    use crate::{
+        clock::SimulatedClock,
+        hero::{GameCommand, HeroState},
+        utils::to_player_name,
+        Camera, Cell, Direction, Entity, GameColors, GameEvent, IntoDirection, NpcStrategy,
+        PlayerId, Tile,
+    };
+    use anyhow::Result as AppResult;
+    use itertools::Itertools;
+    use rand::seq::IndexedRandom;
+    use rand::{RngExt, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+    use std::collections::HashSet;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    fn kill_hero(game: &mut Game, hero_id: PlayerId) {
+        let hero = game.hero_mut(&hero_id).unwrap();
+        hero.start(vec![], false);
+        hero.state = HeroState::Dead {
+            duration: Duration::from_secs(1),
+            instant: Instant::now() - 2 * Game::RESPAWN_INTERVAL,
+        };
+        hero.record_death();
+    }
+
+    /// Checks that the indexes of the game agree with its heros. `deaths`
+    /// counts the deaths of the heros which already left, per room.
+    fn assert_consistent(game: &Game, deaths: &[usize; MAX_MAZE_ID]) {
+        let names = game
+            .heros
+            .values()
+            .map(|hero| hero.name().to_string())
+            .collect::<HashSet<_>>();
+        assert_eq!(names.len(), game.heros.len());
+        assert_eq!(names, game.taken_names);
+
+        for (maze_id, room) in game.hero_rooms.iter().enumerate() {
+            assert!(room.iter().all_unique());
+            for hero_id in room {
+                let hero = game.get_hero(hero_id).unwrap();
+                assert_eq!(hero.maze_id(), maze_id);
+            }
+        }
+
+        for hero in game.heros.values() {
+            let in_room = game.hero_rooms[hero.maze_id()].contains(&hero.id());
+            assert_eq!(in_room, hero.has_won().is_none());
+        }
+
+        assert!(game
+            .heros
+            .keys()
+            .all(|id| game.top_heros_map.contains_key(id)));
+        assert!(
+            game.top_heros_map
+                .keys()
+                .filter(|id| !game.heros.contains_key(id))
+                .count()
+                <= Game::MAX_DEPARTED_RECORDS
+        );
+        assert_eq!(game.top_heros.len(), game.top_heros_map.len());
+        assert!(game
+            .personal_bests
+            .keys()
+            .all(|id| game.heros.contains_key(id)));
+
+        // Every attempt either passed, failed or is still in progress.
+        for maze in game.mazes.iter() {
+            let (passed, attempted) = maze.success_rate;
+            let in_progress = game
+                .heros
+                .values()
+                .filter(|hero| {
+                    hero.maze_id() == maze.id() && !hero.is_dead() && hero.has_won().is_none()
+                })
+                .count();
+            let failed = deaths[maze.id()]
+                + game
+                    .heros
+                    .values()
+                    .map(|hero| hero.deaths_by_room()[maze.id()])
+                    .sum::<usize>();
+            assert_eq!(attempted, passed + failed + in_progress);
+        }
+    }
+
+    #[test]
+    fn test_player_lifecycle() -> AppResult<()> {
+        let rng = &mut ChaCha8Rng::seed_from_u64(0);
+        let mut game = Game::with_config(GameConfig {
+            seed: Some(0),
+            ..Default::default()
+        })?;
+        let mut deaths = [0; MAX_MAZE_ID];
+        let directions = [
+            Direction::North,
+            Direction::East,
+            Direction::South,
+            Direction::West,
+        ];
+
+        for _ in 0..2000 {
+            let hero_ids = game.heros.keys().copied().sorted().collect::<Vec<_>>();
+            let hero_id = hero_ids.choose(rng).copied();
+
+            match (rng.random_range(0..8), hero_id) {
+                (0, _) | (_, None) => {
+                    let name = ["hero", "theseus", "ariadne"].choose(rng).unwrap();
+                    if rng.random_bool(0.5) {
+                        game.add_player(PlayerId::new_v4(), name);
+                    } else {
+                        game.add_player_with_name(PlayerId::new_v4(), name);
+                    }
+                }
+                (1, Some(hero_id)) => {
+                    let hero = game.get_hero(&hero_id).unwrap();
+                    for (deaths, hero_deaths) in deaths.iter_mut().zip(hero.deaths_by_room()) {
+                        *deaths += hero_deaths;
+                    }
+                    game.remove_player(&hero_id);
+                }
+                (2, Some(hero_id)) => {
+                    if game.get_hero(&hero_id).unwrap().is_disconnected() {
+                        game.reconnect_player(&hero_id);
+                    } else {
+                        game.disconnect_player(&hero_id);
+                    }
+                }
+                (3, Some(hero_id)) => {
+                    let hero = game.get_hero(&hero_id).unwrap();
+                    if matches!(hero.state, HeroState::InMaze { .. }) {
+                        kill_hero(&mut game, hero_id);
+                    }
+                }
+                (4, Some(hero_id)) => {
+                    // Step onto an entrance or an exit of the room.
+                    let maze = game.get_maze(game.get_hero(&hero_id).unwrap().maze_id());
+                    let target = *maze
+                        .entrance_positions()
+                        .iter()
+                        .chain(maze.exit_positions())
+                        .collect::<Vec<_>>()
+                        .choose(rng)
+                        .unwrap();
+                    let steps = directions
+                        .iter()
+                        .map(|direction| (target.into_direction(direction), direction.opposite()))
+                        .filter(|&(position, _)| maze.is_valid_position(position))
+                        .collect::<Vec<_>>();
+                    if let Some(&(position, direction)) = steps.choose(rng) {
+                        let hero = game.hero_mut(&hero_id).unwrap();
+                        hero.set_position(position);
+                        hero.rest();
+                        game.handle_command(&GameCommand::Move { direction }, hero_id);
+                    }
+                }
+                (5, _) => {
+                    for hero in game.heros.values_mut().map(Arc::make_mut) {
+                        if let HeroState::Victory { duration, .. } = hero.state {
+                            hero.state = HeroState::Victory {
+                                duration,
+                                instant: Instant::now() - 2 * Game::RESPAWN_INTERVAL,
+                            };
+                        }
+                    }
+                    game.update();
+                }
+                (_, Some(hero_id)) => {
+                    game.hero_mut(&hero_id).unwrap().rest();
+                    let direction = *directions.choose(rng).unwrap();
+                    game.handle_command(&GameCommand::Move { direction }, hero_id);
+                }
+            }
+
+            assert_consistent(&game, &deaths);
+        }
+
+        // Make sure the sequence went through some rooms.
+        assert!(game.mazes[1].success_rate.0 > 0);
+
+        let hero_ids = game.heros.keys().copied().collect::<Vec<_>>();
+        for hero_id in hero_ids {
+            game.remove_player(&hero_id);
+        }
+        assert!(game.taken_names.is_empty());
+        assert!(game.hero_rooms.iter().all(|room| room.is_empty()));
+        // Records of departed heros stay on the leaderboard, up to a bound.
+        assert!(!game.top_heros.is_empty());
+        assert!(game.top_heros.len() <= Game::MAX_DEPARTED_RECORDS);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reset_room() -> AppResult<()> {
+        let mut game = Game::new()?;
+        let hero_id = PlayerId::new_v4();
+        game.add_player(hero_id, "hero");
+        game.hero_rooms[0].retain(|id| *id != hero_id);
+        game.hero_rooms[1].push(hero_id);
+        let hero = game.hero_mut(&hero_id).unwrap();
+        hero.start(vec![], false);
+        hero.set_maze_id(1);
+
+        game.set_minotaurs_in_maze(1, 3)?;
+        assert_eq!(game.minotaurs_in_maze(1), 3);
+        assert_eq!(game.top_minotaurs().len(), game.minotaurs.len());
+        game.set_minotaurs_in_maze(1, 2)?;
+        assert_eq!(game.minotaurs_in_maze(1), 2);
+        assert!(game
+            .set_minotaurs_in_maze(1, MAX_MINOTAURS_PER_ROOM + 1)
+            .is_err());
+
+        game.reset_room(1)?;
+        assert_eq!(game.minotaurs_in_maze(1), 2);
+        assert!(game.minotaur_rooms[1]
+            .iter()
+            .all(|id| game.get_minotaur(id).unwrap().maze_id() == 1));
+        let hero = game.get_hero(&hero_id).unwrap();
+        assert_eq!(hero.maze_id(), 1);
+        assert!(game.get_maze(1).is_entrance_position(hero.position()));
+        assert!(game.reset_room(MAX_MAZE_ID).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reset_last_room_after_victory() -> AppResult<()> {
+        let mut game = Game::new()?;
+        let hero_id = PlayerId::new_v4();
+        game.add_player(hero_id, "hero");
+        let last_maze_id = MAX_MAZE_ID - 1;
+        // Heros who won leave the rooms, but stay in the last one until they
+        // start over.
+        game.hero_rooms[0].retain(|id| *id != hero_id);
+        let hero = game.hero_mut(&hero_id).unwrap();
+        hero.start(vec![], false);
+        hero.set_maze_id(last_maze_id);
+        hero.update_past_visible_positions(HashSet::new());
+        hero.state = HeroState::Victory {
+            duration: Duration::from_secs(60),
+            instant: Instant::now(),
+        };
+
+        game.reset_room(last_maze_id)?;
+        game.tiles(hero_id, &Camera::new(120, 40))?;
+        game.draw_minimap(hero_id, 40, 20)?;
+        game.observe(hero_id)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_events() -> AppResult<()> {
+        let mut game = Game::new()?;
+        let hero_id = PlayerId::new_v4();
+        game.add_player(hero_id, "hero");
+        let name = game.get_hero(&hero_id).unwrap().name().to_string();
+
+        let minotaur_id = game.minotaur_rooms[1][0];
+        let position = game.get_minotaur(&minotaur_id).unwrap().position();
+        game.hero_rooms[0].retain(|id| *id != hero_id);
+        game.hero_rooms[1].push(hero_id);
+        let hero = game.hero_mut(&hero_id).unwrap();
+        hero.start(vec![], false);
+        hero.set_maze_id(1);
+        hero.set_position(position);
+        game.update();
+        game.remove_player(&hero_id);
+
+        let events = game.drain_events();
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events[0],
+            GameEvent::HeroJoined {
+                hero_id,
+                name: name.clone()
+            }
+        );
+        assert!(matches!(
+            events[1],
+            GameEvent::HeroDied { maze_id: 1, minotaur_id: id, .. } if id == minotaur_id
+        ));
+        assert_eq!(events[2], GameEvent::HeroLeft { hero_id, name });
+        assert!(game.drain_events().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_observe() -> AppResult<()> {
+        let mut game = Game::new()?;
+        let hero_id = PlayerId::new_v4();
+        let other_id = PlayerId::new_v4();
+        game.add_player(hero_id, "hero");
+        game.add_player(other_id, "other");
+
+        let minotaur_id = game.minotaur_rooms[1][0];
+        let position = game.get_minotaur(&minotaur_id).unwrap().position();
+        for id in [hero_id, other_id] {
+            game.hero_rooms[0].retain(|hero_id| *hero_id != id);
+            game.hero_rooms[1].push(id);
+            let hero = game.hero_mut(&id).unwrap();
+            hero.set_maze_id(1);
+            hero.set_position(position);
+        }
+        let hero = game.get_hero(&hero_id).unwrap();
+        let (direction, view) = (hero.direction(), hero.view());
+        let visible_positions =
+            game.mazes[1].get_and_cache_visible_positions(position, direction, view);
+        game.hero_mut(&hero_id)
+            .unwrap()
+            .update_past_visible_positions(visible_positions);
+
+        let observation = game.observe(hero_id)?;
+        assert_eq!(observation.maze_id, 1);
+        assert_eq!(observation.position, position);
+        assert!(observation.visible.contains(&(position, Cell::Floor)));
+        assert!(observation
+            .visible
+            .iter()
+            .is_sorted_by_key(|(position, _)| *position));
+        assert!(observation.minotaurs.iter().any(|m| m.id == minotaur_id));
+        assert_eq!(observation.heros.len(), 1);
+        assert_eq!(observation.heros[0].id, other_id);
+
+        // Disconnected heros are not shown to anybody.
+        game.disconnect_player(&other_id);
+        assert!(game.observe(hero_id)?.heros.is_empty());
+        assert!(game.observe(PlayerId::new_v4()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_large_rooms() -> AppResult<()> {
+        let mut game = Game::with_config(GameConfig {
+            seed: Some(0),
+            room_size: Some((100, 60)),
+            ..Default::default()
+        })?;
+        let hero_id = PlayerId::new_v4();
+        game.add_player(hero_id, "hero");
+
+        let hero = game.get_hero(&hero_id).unwrap();
+        let bounds = game.get_maze(0).dimensions();
+        assert!(bounds.0 > 400 && bounds.1 > 240);
+
+        let mut camera = Camera::new(120, 40);
+        camera.follow(0, hero.position(), bounds);
+        let tiles = game.tiles(hero_id, &camera)?;
+        assert_eq!(tiles.dimensions(), (120, 40));
+        let (x, y) = camera.to_view(hero.position(), bounds).unwrap();
+        assert_eq!(tiles.get(x, y), Some(Tile::Hero));
+        assert!(tiles
+            .iter()
+            .any(|(_, tile)| matches!(tile, Tile::Floor { .. })));
+
+        let minimap = game.draw_minimap(hero_id, 24, 16)?;
+        assert!(minimap.width() <= 24 && minimap.height() <= 16);
+        assert!(minimap.iter().any(|(_, pixel)| *pixel == GameColors::HERO));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_minimap_landmarks() -> AppResult<()> {
+        let now = Instant::now();
+        let clock = SimulatedClock::install(now);
+        let mut game = Game::with_config(GameConfig {
+            seed: Some(0),
+            ..Default::default()
+        })?;
+        let hero_id = PlayerId::new_v4();
+        game.add_player(hero_id, "hero");
+        let has_exit = |game: &Game| -> AppResult<bool> {
+            let minimap = game.draw_minimap(hero_id, 24, 16)?;
+            let found = minimap.iter().any(|(_, pixel)| *pixel == GameColors::EXIT);
+            Ok(found)
+        };
+        assert!(!has_exit(&game)?);
+
+        let exit = game.get_maze(0).exit_positions().clone();
+        let hero = game.hero_mut(&hero_id).unwrap();
+        hero.update_past_visible_positions(exit.into_iter().collect());
+        let minimap_duration = hero.minimap_duration();
+        assert!(minimap_duration > hero.past_visibility_duration());
+        assert!(has_exit(&game)?);
+
+        // Forgotten areas leave the minimap, the sooner the less memory.
+        clock.set(now + minimap_duration);
+        assert!(!has_exit(&game)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_npcs_explore() -> AppResult<()> {
+        let mut now = Instant::now();
+        let clock = SimulatedClock::install(now);
+        let mut game = Game::new()?;
+        let npc_ids = [NpcStrategy::WallFollower, NpcStrategy::Frontier]
+            .map(|strategy| game.add_npc(strategy));
+        let starts = npc_ids.map(|id| game.get_hero(&id).unwrap().position());
+
+        for _ in 0..400 {
+            now += Game::update_time_step();
+            clock.set(now);
+            game.update();
+        }
+
+        for (npc_id, start) in npc_ids.iter().zip(starts) {
+            assert!(game.is_npc(npc_id));
+            let hero = game.get_hero(npc_id).unwrap();
+            assert!(hero.stats().steps > 10);
+            assert_ne!(hero.position(), start);
+        }
+
+        game.remove_player(&npc_ids[0]);
+        assert!(!game.is_npc(&npc_ids[0]));
+        assert_eq!(game.npcs().count(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_new_record_event() -> AppResult<()> {
+        let mut game = Game::new()?;
+        let leader_id = PlayerId::new_v4();
+        let other_id = PlayerId::new_v4();
+        game.add_player(leader_id, "leader");
+        game.add_player(other_id, "other");
+        for (id, maze_id) in [(leader_id, 2), (other_id, 1)] {
+            let hero = game.hero_mut(&id).unwrap();
+            hero.start(vec![], false);
+            hero.set_maze_id(maze_id);
+        }
+        game.drain_events();
+
+        game.update_hero_record(leader_id);
+        game.update_hero_record(other_id);
+        let events = game.drain_events();
+        assert_eq!(events.len(), 1);
+        assert!(matches!(
+            events[0],
+            GameEvent::NewRecord { hero_id, maze_id: 2, .. } if hero_id == leader_id
+        ));
+
+        game.hero_mut(&other_id).unwrap().set_maze_id(3);
+        game.update_hero_record(other_id);
+        assert!(matches!(
+            game.drain_events()[..],
+            [GameEvent::NewRecord { hero_id, maze_id: 3, .. }] if hero_id == other_id
+        ));
+
+        Ok(())
+    }
 
     #[test]
     fn test_top_heros() -> AppResult<()> {
@@ -707,17 +1913,281 @@ mod tests {
                     to_player_name(rng, "name"),
                     rng.random_range(0..=MAX_MAZE_ID),
                     Duration::from_millis(rng.random_range(15000..150000)),
+                    rng.random_bool(0.5),
                 ),
             );
         }
 
+        // Deepest room first, then fastest.
+        let expected = game
+            .top_heros_map
+            .values()
+            .map(|&(_, maze_id, timer, _)| (maze_id, timer))
+            .sorted_by_key(|&(maze_id, timer)| (std::cmp::Reverse(maze_id), timer))
+            .take(Game::MAX_DEPARTED_RECORDS)
+            .collect_vec();
+
         game.update_top_heros();
-        for index in 0..game.top_heros.len() {
-            let (_, _, maze_id, timer) = game.top_heros[index];
-            let (_, _, next_maze_id, next_timer) = game.top_heros[index];
+        // Only the best records of heros no longer in the game are kept.
+        assert_eq!(game.top_heros.len(), Game::MAX_DEPARTED_RECORDS);
+        assert_eq!(game.top_heros_map.len(), Game::MAX_DEPARTED_RECORDS);
+        assert_eq!(
+            game.top_heros
+                .iter()
+                .map(|&(_, _, maze_id, timer, _)| (maze_id, timer))
+                .collect_vec(),
+            expected
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_departed_hero_keeps_record() -> AppResult<()> {
+        let mut game = Game::new()?;
+        let hero_id = PlayerId::new_v4();
+        game.add_player(hero_id, "leaver");
+        game.remove_player(&hero_id);
+
+        assert!(game.get_hero(&hero_id).is_none());
+        assert!(game.top_heros().iter().any(|(id, ..)| *id == hero_id));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_seeded_mazes() -> AppResult<()> {
+        let game = Game::with_config(GameConfig::time_trial(42))?;
+        let mut other = Game::with_config(GameConfig::time_trial(42))?;
+        // Reset rooms are rebuilt from the seed of the trial.
+        other.reset_room(3)?;
+
+        for maze_id in 0..MAX_MAZE_ID {
+            let maze = game.get_maze(maze_id);
+            let other_maze = other.get_maze(maze_id);
+            assert_eq!(maze.walls(), other_maze.walls());
+            assert_eq!(maze.power_up_positions, other_maze.power_up_positions);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_paths_recorded_in_time_trials() -> AppResult<()> {
+        for (config, recorded) in [
+            (GameConfig::default(), false),
+            (GameConfig::time_trial(42), true),
+        ] {
+            let mut game = Game::with_config(config)?;
+            let hero_id = PlayerId::new_v4();
+            game.add_player(hero_id, "hero");
+            game.handle_command(&GameCommand::TurnClockwise, hero_id);
+            let hero = game.hero_mut(&hero_id).unwrap();
+            let position = hero.position();
+            hero.set_position(position);
+            assert_eq!(!hero.path().is_empty(), recorded);
+        }
 
-            assert!(maze_id > next_maze_id || timer <= next_timer);
+        Ok(())
+    }
+
+    #[test]
+    fn test_death_policies() -> AppResult<()> {
+        let mut game = Game::with_config(GameConfig {
+            death_policy: DeathPolicy::Lives { lives: 2 },
+            ..Default::default()
+        })?;
+        let hero_id = PlayerId::new_v4();
+        game.add_player(hero_id, "hero");
+
+        kill_hero(&mut game, hero_id);
+        game.update();
+        let hero = game.get_hero(&hero_id).unwrap();
+        assert!(matches!(hero.state, HeroState::InMaze { .. }));
+        assert_eq!(hero.stats().deaths, 1);
+
+        kill_hero(&mut game, hero_id);
+        game.update();
+        let hero = game.get_hero(&hero_id).unwrap();
+        assert_eq!(hero.state, HeroState::WaitingToStart);
+        assert_eq!(hero.stats().deaths, 0);
+
+        let mut game = Game::with_config(GameConfig {
+            death_policy: DeathPolicy::Checkpoints,
+            ..Default::default()
+        })?;
+        game.add_player(hero_id, "hero");
+        let shrine = game.get_maze(2).shrine_position().unwrap();
+        game.hero_mut(&hero_id).unwrap().set_checkpoint(2, shrine);
+
+        kill_hero(&mut game, hero_id);
+        game.update();
+        let hero = game.get_hero(&hero_id).unwrap();
+        assert_eq!((hero.maze_id(), hero.position()), (2, shrine));
+        assert!(game.hero_rooms[2].contains(&hero_id));
+        assert!(!game.hero_rooms[0].contains(&hero_id));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_disconnected_hero_run_is_paused() -> AppResult<()> {
+        let now = Instant::now();
+        let clock = SimulatedClock::install(now);
+        let mut game = Game::new()?;
+        let hero_id = PlayerId::new_v4();
+        game.add_player(hero_id, "hero");
+        game.hero_mut(&hero_id).unwrap().start(vec![], false);
+
+        clock.set(now + Duration::from_secs(10));
+        game.disconnect_player(&hero_id);
+        clock.set(now + Duration::from_secs(70));
+        let hero = game.get_hero(&hero_id).unwrap();
+        assert_eq!(hero.elapsed_duration_from_start(), Duration::from_secs(10));
+
+        game.reconnect_player(&hero_id);
+        clock.set(now + Duration::from_secs(75));
+        let hero = game.get_hero(&hero_id).unwrap();
+        assert_eq!(hero.elapsed_duration_from_start(), Duration::from_secs(15));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_seeded_games_play_out_the_same() -> AppResult<()> {
+        let now = Instant::now();
+        let clock = SimulatedClock::install(now);
+        let config = GameConfig {
+            seed: Some(7),
+            ..Default::default()
+        };
+        let mut game = Game::with_config(config.clone())?;
+        let mut other = Game::with_config(config)?;
+        let hero_id = PlayerId::new_v4();
+        game.add_player(hero_id, "hero");
+        other.add_player(hero_id, "hero");
+        assert_eq!(
+            game.get_hero(&hero_id).unwrap().position(),
+            other.get_hero(&hero_id).unwrap().position()
+        );
+
+        let positions = |game: &Game| {
+            game.minotaur_rooms
+                .iter()
+                .flatten()
+                .map(|id| (*id, game.minotaurs[id].position()))
+                .collect_vec()
+        };
+        let start = positions(&game);
+        for tick in 1..=200 {
+            clock.set(now + Game::update_time_step() * tick);
+            game.update();
+            other.update();
+            assert_eq!(positions(&game), positions(&other));
         }
+        assert_ne!(positions(&game), start);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_disconnected_hero_is_not_caught() -> AppResult<()> {
+        let mut game = Game::new()?;
+        let hero_id = PlayerId::new_v4();
+        game.add_player(hero_id, "hero");
+
+        let minotaur_id = game.minotaur_rooms[1][0];
+        let position = game.get_minotaur(&minotaur_id).unwrap().position();
+        game.hero_rooms[0].retain(|id| *id != hero_id);
+        game.hero_rooms[1].push(hero_id);
+        let hero = game.hero_mut(&hero_id).unwrap();
+        hero.start(vec![], false);
+        hero.set_maze_id(1);
+        hero.set_position(position);
+
+        game.disconnect_player(&hero_id);
+        game.update();
+        assert!(!game.get_hero(&hero_id).unwrap().is_dead());
+
+        game.reconnect_player(&hero_id);
+        let position = game.get_minotaur(&minotaur_id).unwrap().position();
+        game.hero_mut(&hero_id).unwrap().set_position(position);
+        game.update();
+        assert!(game.get_hero(&hero_id).unwrap().is_dead());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_snapshots_share_what_does_not_change() -> AppResult<()> {
+        let now = Instant::now();
+        let clock = SimulatedClock::install(now);
+        let mut game = Game::with_config(GameConfig {
+            seed: Some(7),
+            ..Default::default()
+        })?;
+        let hero_ids = (0..200).map(|_| PlayerId::new_v4()).collect_vec();
+        for &hero_id in hero_ids.iter() {
+            game.add_player(hero_id, "hero");
+        }
+
+        let snapshot = game.clone();
+        for tick in 1..=20 {
+            clock.set(now + Game::update_time_step() * tick);
+            game.update();
+        }
+        let moving_id = hero_ids[0];
+        game.handle_command(
+            &GameCommand::Move {
+                direction: Direction::East,
+            },
+            moving_id,
+        );
+
+        for maze_id in 0..MAX_MAZE_ID {
+            assert!(std::ptr::eq(
+                game.get_maze(maze_id).walls(),
+                snapshot.get_maze(maze_id).walls()
+            ));
+        }
+        // Only the hero which moved was copied.
+        for hero_id in hero_ids.iter() {
+            assert_eq!(
+                Arc::ptr_eq(&game.heros[hero_id], &snapshot.heros[hero_id]),
+                *hero_id != moving_id
+            );
+        }
+        assert!(snapshot.get_hero(&moving_id).unwrap().state == HeroState::WaitingToStart);
+
+        Ok(())
+    }
+
+    #[test]
+    #[ignore = "measures wall-clock time, run with --ignored on an idle machine"]
+    fn test_tick_time_with_many_players() -> AppResult<()> {
+        // A tick updates the game and takes a snapshot of it for the sessions,
+        // which are kept alive like the ones being drawn.
+        let tick_time = |players: usize| -> AppResult<Duration> {
+            let mut game = Game::with_config(GameConfig {
+                seed: Some(7),
+                ..Default::default()
+            })?;
+            for _ in 0..players {
+                game.add_player(PlayerId::new_v4(), "hero");
+            }
+            let mut snapshots = vec![];
+            let start = Instant::now();
+            for _ in 0..20 {
+                game.update();
+                snapshots.push(game.clone());
+            }
+            Ok(start.elapsed() / 20)
+        };
+
+        // Snapshots copy none of the heros standing still, so a tick stays
+        // well within the update step with many more players than a server
+        // usually has.
+        assert!(tick_time(1000)? < Game::update_time_step());
 
         Ok(())
     }
@@ -0,0 +1,54 @@
+/// A plain grid of cells, stored in rows.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Grid<T> {
+    width: u32,
+    height: u32,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: u32, height: u32, value: T) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![value; (width * height) as usize],
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Option<&T> {
+        if x < self.width && y < self.height {
+            self.cells.get((y * self.width + x) as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Sets the cell at `(x, y)`, ignoring positions out of the grid.
+    pub fn set(&mut self, x: u32, y: u32, value: T) {
+        if x < self.width && y < self.height {
+            self.cells[(y * self.width + x) as usize] = value;
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ((u32, u32), &T)> + '_ {
+        self.cells.iter().enumerate().map(|(idx, value)| {
+            let idx = idx as u32;
+            ((idx % self.width, idx / self.width), value)
+        })
+    }
+}
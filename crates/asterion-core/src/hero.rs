@@ -1,4 +1,10 @@
-use crate::{entity::Entity, power_up::PowerUp, Direction, PlayerId, Position};
+use crate::{
+    clock,
+    entity::Entity,
+    power_up::PowerUp,
+    time_trial::{GhostSample, GhostTarget},
+    Direction, PlayerId, Position, MAX_MAZE_ID,
+};
 use std::{
     collections::{HashMap, HashSet},
     time::{Duration, Instant},
@@ -6,11 +12,14 @@ use std::{
 use strum_macros::Display;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GameCommand {
     Move { direction: Direction },
     TurnClockwise,
     TurnCounterClockwise,
     CycleUiOptions,
+    CycleGhostTarget,
+    ToggleAnnouncements,
 }
 
 #[derive(Debug, Clone, Copy, Display, PartialEq)]
@@ -29,21 +38,46 @@ pub enum HeroState {
     },
 }
 #[derive(Debug, Clone, Copy, Display, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UiOptions {
     Dark,
     Light,
+    HighContrast,
+    /// One of the themes loaded from the config.
+    Custom {
+        index: usize,
+    },
 }
 
 impl UiOptions {
-    pub fn next(&self) -> Self {
-        match self {
+    /// The next theme, cycling through the built-in ones and then the
+    /// `custom_themes` loaded from the config.
+    pub fn next(&self, custom_themes: usize) -> Self {
+        match *self {
             Self::Dark => Self::Light,
-            Self::Light => Self::Dark,
+            Self::Light => Self::HighContrast,
+            Self::HighContrast if custom_themes > 0 => Self::Custom { index: 0 },
+            Self::Custom { index } if index + 1 < custom_themes => {
+                Self::Custom { index: index + 1 }
+            }
+            Self::HighContrast | Self::Custom { .. } => Self::Dark,
         }
     }
 }
 
-#[derive(Debug)]
+/// Statistics of the current run, reset when the hero starts over.
+#[derive(Debug, Clone, Default)]
+pub struct RunStats {
+    /// Time at which each room was first exited.
+    pub splits: Vec<Duration>,
+    pub steps: usize,
+    pub power_ups: usize,
+    /// Chases survived after a minotaur got next to the hero.
+    pub near_misses: usize,
+    pub deaths: usize,
+}
+
+#[derive(Debug, Clone)]
 pub struct Hero {
     id: PlayerId,
     name: String,
@@ -57,7 +91,22 @@ pub struct Hero {
     past_visible_positions: HashMap<usize, HashMap<Position, Instant>>,
     last_move_time: Instant,
     collected_power_ups: HashMap<usize, Vec<Position>>,
+    power_up_history: Vec<PowerUp>,
+    checkpoint: Option<(usize, Position)>,
     ui_options: UiOptions,
+    stats: RunStats,
+    best_splits: Vec<Duration>,
+    deaths_by_room: [usize; MAX_MAZE_ID],
+    close_calls: HashSet<PlayerId>,
+    victories: usize,
+    /// When the player of the hero lost their connection, the run being
+    /// paused until they are back.
+    disconnected_at: Option<Instant>,
+    path: Vec<GhostSample>,
+    /// Whether the path of the run is recorded, to be raced as a ghost.
+    records_path: bool,
+    ghost_target: GhostTarget,
+    announcements_muted: bool,
 }
 
 impl Hero {
@@ -79,9 +128,21 @@ impl Hero {
             speed: Self::INITIAL_SPEED,
             memory: Self::INITIAL_MEMORY,
             past_visible_positions: HashMap::new(),
-            last_move_time: Instant::now(),
+            last_move_time: clock::now(),
             collected_power_ups: HashMap::new(),
+            power_up_history: vec![],
+            checkpoint: None,
             ui_options: UiOptions::Dark,
+            stats: RunStats::default(),
+            best_splits: vec![],
+            deaths_by_room: [0; MAX_MAZE_ID],
+            close_calls: HashSet::new(),
+            victories: 0,
+            disconnected_at: None,
+            path: vec![],
+            records_path: false,
+            ghost_target: GhostTarget::Leader,
+            announcements_muted: false,
         }
     }
 
@@ -94,18 +155,199 @@ impl Hero {
         self.speed = Self::INITIAL_SPEED;
         self.memory = Self::INITIAL_MEMORY;
         self.past_visible_positions.clear();
-        self.last_move_time = Instant::now();
+        self.last_move_time = clock::now();
         self.collected_power_ups.clear();
+        self.power_up_history.clear();
+        self.checkpoint = None;
+        self.stats = RunStats::default();
+        self.close_calls.clear();
+        self.path.clear();
+    }
+
+    /// Starts a new run, comparing its splits against `best_splits` and
+    /// recording its path if `record_path`.
+    pub fn start(&mut self, best_splits: Vec<Duration>, record_path: bool) {
+        self.state = HeroState::InMaze {
+            instant: clock::now(),
+        };
+        self.best_splits = best_splits;
+        self.records_path = record_path;
+        if record_path {
+            self.path.push(GhostSample {
+                elapsed: Duration::ZERO,
+                maze_id: self.maze_id,
+                position: self.position,
+            });
+        }
+    }
+
+    /// Brings a dead hero back to life at the given position, keeping its
+    /// power ups. The run timer keeps running from the original start.
+    pub fn respawn(&mut self, maze_id: usize, position: Position) {
+        if let HeroState::Dead { duration, instant } = self.state {
+            self.state = HeroState::InMaze {
+                instant: instant - duration,
+            };
+        }
+        self.maze_id = maze_id;
+        self.direction = Direction::East;
+        self.close_calls.clear();
+        self.set_position(position);
+    }
+
+    pub fn checkpoint(&self) -> Option<(usize, Position)> {
+        self.checkpoint
     }
 
-    pub fn cycle_ui_options(&mut self) {
-        self.ui_options = self.ui_options.next();
+    pub fn set_checkpoint(&mut self, maze_id: usize, position: Position) {
+        self.checkpoint = Some((maze_id, position));
+    }
+
+    /// Drops what the hero knows about a maze which has been rebuilt.
+    pub fn forget_maze(&mut self, maze_id: usize) {
+        self.past_visible_positions.remove(&maze_id);
+        self.collected_power_ups.remove(&maze_id);
+    }
+
+    pub fn lose_last_power_up(&mut self) {
+        match self.power_up_history.pop() {
+            Some(PowerUp::Speed) => self.speed = self.speed.saturating_sub(1),
+            Some(PowerUp::Vision) => {
+                self.vision = self.vision.saturating_sub(1).max(Self::INITIAL_VISION)
+            }
+            Some(PowerUp::Memory) => self.memory = self.memory.saturating_sub(1),
+            None => {}
+        }
+    }
+
+    pub fn cycle_ui_options(&mut self, custom_themes: usize) {
+        self.ui_options = self.ui_options.next(custom_themes);
+    }
+
+    pub fn ui_options(&self) -> UiOptions {
+        self.ui_options
+    }
+
+    pub fn set_ui_options(&mut self, ui_options: UiOptions) {
+        self.ui_options = ui_options;
+    }
+
+    pub fn cycle_ghost_target(&mut self) {
+        self.ghost_target = self.ghost_target.next();
+    }
+
+    pub fn set_ghost_target(&mut self, ghost_target: GhostTarget) {
+        self.ghost_target = ghost_target;
+    }
+
+    pub fn ghost_target(&self) -> GhostTarget {
+        self.ghost_target
+    }
+
+    pub fn toggle_announcements(&mut self) {
+        self.announcements_muted = !self.announcements_muted;
+    }
+
+    pub fn set_announcements_muted(&mut self, announcements_muted: bool) {
+        self.announcements_muted = announcements_muted;
+    }
+
+    pub fn announcements_muted(&self) -> bool {
+        self.announcements_muted
+    }
+
+    pub fn stats(&self) -> &RunStats {
+        &self.stats
+    }
+
+    /// Splits of the personal best at the time the current run started.
+    pub fn best_splits(&self) -> &Vec<Duration> {
+        &self.best_splits
+    }
+
+    pub fn deaths_by_room(&self) -> &[usize; MAX_MAZE_ID] {
+        &self.deaths_by_room
+    }
+
+    pub fn record_split(&mut self, maze_id: usize, elapsed: Duration) {
+        if self.stats.splits.len() == maze_id {
+            self.stats.splits.push(elapsed);
+        }
+    }
+
+    pub fn increase_steps(&mut self) {
+        self.stats.steps += 1;
+    }
+
+    pub fn victories(&self) -> usize {
+        self.victories
+    }
+
+    pub fn record_victory(&mut self) {
+        self.victories += 1;
+    }
+
+    pub fn record_death(&mut self) {
+        self.stats.deaths += 1;
+        self.deaths_by_room[self.maze_id] += 1;
+        self.close_calls.clear();
+    }
+
+    /// Whether [`Hero::track_near_miss`] would change anything.
+    pub fn tracks_near_miss(
+        &self,
+        minotaur_id: PlayerId,
+        is_chasing: bool,
+        is_close: bool,
+    ) -> bool {
+        let close_call = self.close_calls.contains(&minotaur_id);
+        if is_chasing {
+            is_close && !close_call
+        } else {
+            close_call
+        }
+    }
+
+    /// A near miss is counted when a minotaur which got next to the hero
+    /// stops chasing it.
+    pub fn track_near_miss(&mut self, minotaur_id: PlayerId, is_chasing: bool, is_close: bool) {
+        if is_chasing {
+            if is_close {
+                self.close_calls.insert(minotaur_id);
+            }
+        } else if self.close_calls.remove(&minotaur_id) {
+            self.stats.near_misses += 1;
+        }
+    }
+
+    pub fn path(&self) -> &Vec<GhostSample> {
+        &self.path
     }
 
     pub fn is_dead(&self) -> bool {
         matches!(self.state, HeroState::Dead { .. })
     }
 
+    /// Disconnected heros are frozen, hidden and ignored by the minotaurs
+    /// while waiting for their player to reconnect.
+    pub fn is_disconnected(&self) -> bool {
+        self.disconnected_at.is_some()
+    }
+
+    /// Pauses the run while the player is away, shifting its start by the
+    /// time spent disconnected once they are back.
+    pub fn set_disconnected(&mut self, disconnected: bool) {
+        if disconnected {
+            self.disconnected_at.get_or_insert_with(clock::now);
+        } else if let Some(disconnected_at) = self.disconnected_at.take() {
+            if let HeroState::InMaze { instant } = self.state {
+                self.state = HeroState::InMaze {
+                    instant: instant + clock::elapsed(disconnected_at),
+                };
+            }
+        }
+    }
+
     pub fn has_won(&self) -> Option<Duration> {
         match self.state {
             HeroState::Victory { duration, .. } => Some(duration),
@@ -120,7 +362,10 @@ impl Hero {
     pub fn elapsed_duration_from_start(&self) -> Duration {
         match self.state {
             HeroState::WaitingToStart => Duration::from_millis(0),
-            HeroState::InMaze { instant } => instant.elapsed(),
+            HeroState::InMaze { instant } => match self.disconnected_at {
+                Some(disconnected_at) => disconnected_at.saturating_duration_since(instant),
+                None => clock::elapsed(instant),
+            },
             HeroState::Dead { duration, .. } => duration,
             HeroState::Victory { duration, .. } => duration,
         }
@@ -131,22 +376,34 @@ impl Hero {
             return false;
         }
 
-        self.last_move_time.elapsed() >= self.movement_recovery_duration()
+        clock::elapsed(self.last_move_time) >= self.movement_recovery_duration()
+    }
+
+    /// Lets the hero move right away, without waiting for its recovery.
+    #[cfg(test)]
+    pub(crate) fn rest(&mut self) {
+        self.last_move_time = clock::now() - self.movement_recovery_duration();
     }
 
     pub fn past_visibility_duration(&self) -> Duration {
         Duration::from_secs_f32(10.0 + 10.0 * self.memory as f32)
     }
 
+    /// How long explored areas stay on the minimap, after they faded from the
+    /// view of the room.
+    pub fn minimap_duration(&self) -> Duration {
+        3 * self.past_visibility_duration()
+    }
+
     pub fn update_past_visible_positions(&mut self, visible_positions: HashSet<Position>) {
-        let duration = self.past_visibility_duration();
+        let duration = self.minimap_duration();
 
         let past_visible_positions = self.past_visible_positions.entry(self.maze_id).or_default();
 
         for &position in visible_positions.iter() {
-            past_visible_positions.insert(position, Instant::now());
+            past_visible_positions.insert(position, clock::now());
         }
-        past_visible_positions.retain(|_, instant| instant.elapsed() < duration);
+        past_visible_positions.retain(|_, instant| clock::elapsed(*instant) < duration);
     }
 
     pub fn past_visible_positions(&self) -> &HashMap<Position, Instant> {
@@ -159,7 +416,17 @@ impl Hero {
 
     pub fn set_position(&mut self, position: Position) {
         self.position = position;
-        self.last_move_time = Instant::now();
+        self.last_move_time = clock::now();
+
+        if let HeroState::InMaze { instant } = self.state {
+            if self.records_path {
+                self.path.push(GhostSample {
+                    elapsed: clock::elapsed(instant),
+                    maze_id: self.maze_id,
+                    position,
+                });
+            }
+        }
     }
 
     pub fn set_maze_id(&mut self, maze_id: usize) {
@@ -170,7 +437,7 @@ impl Hero {
         self.vision -= 1;
     }
 
-    pub fn apply_random_power_up_at_position(&mut self, position: Position) {
+    pub fn apply_random_power_up_at_position(&mut self, position: Position) -> PowerUp {
         let mut available_power_ups = vec![];
 
         if self.speed < Self::MAX_SPEED {
@@ -191,11 +458,15 @@ impl Hero {
             PowerUp::Vision => self.vision = (self.vision + 1).min(Self::MAX_VISION),
             PowerUp::Memory => self.memory += 1,
         }
+        self.power_up_history.push(power_up);
+        self.stats.power_ups += 1;
 
         self.collected_power_ups
             .entry(self.maze_id)
             .and_modify(|e| e.push(position))
             .or_insert(vec![position]);
+
+        power_up
     }
 
     pub fn power_ups_collected_in_maze(&self, maze_id: usize) -> usize {
@@ -242,3 +513,38 @@ impl Entity for Hero {
         self.maze_id
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Hero;
+    use crate::PlayerId;
+
+    #[test]
+    fn test_near_misses() {
+        let mut hero = Hero::new(PlayerId::new_v4(), "hero".to_string(), (0, 0));
+        let minotaur_id = PlayerId::new_v4();
+
+        // Chasing from afar and giving up is not a near miss.
+        hero.track_near_miss(minotaur_id, true, false);
+        hero.track_near_miss(minotaur_id, false, false);
+        assert_eq!(hero.stats().near_misses, 0);
+
+        assert!(hero.tracks_near_miss(minotaur_id, true, true));
+        hero.track_near_miss(minotaur_id, true, true);
+        assert!(!hero.tracks_near_miss(minotaur_id, true, true));
+        assert!(!hero.tracks_near_miss(minotaur_id, true, false));
+        hero.track_near_miss(minotaur_id, true, false);
+        assert_eq!(hero.stats().near_misses, 0);
+        assert!(hero.tracks_near_miss(minotaur_id, false, false));
+        hero.track_near_miss(minotaur_id, false, false);
+        assert_eq!(hero.stats().near_misses, 1);
+        assert!(!hero.tracks_near_miss(minotaur_id, false, false));
+
+        // Getting caught is not a near miss either.
+        hero.track_near_miss(minotaur_id, true, true);
+        hero.record_death();
+        hero.track_near_miss(minotaur_id, false, false);
+        assert_eq!(hero.stats().near_misses, 1);
+        assert_eq!(hero.deaths_by_room()[0], 1);
+    }
+}
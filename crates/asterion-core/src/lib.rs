@@ -1,25 +1,47 @@
 //! Pure game logic for asterion - data and rules with no I/O, UI, or SSH.
 
 mod alarm_level;
+mod camera;
+mod clock;
 mod direction;
 mod entity;
+mod env;
+mod event;
 mod game;
+mod grid;
 mod hero;
 mod maze;
 mod minotaur;
+mod npc;
+mod observation;
 mod power_up;
+mod theme;
+mod tile;
+mod time_trial;
 mod utils;
 mod view;
 
 pub use alarm_level::AlarmLevel;
+pub use camera::Camera;
+pub use clock::SimulatedClock;
 pub use direction::Direction;
 pub use entity::Entity;
-pub use game::{Game, MAX_MAZE_ID, POWER_UPS_PER_ROOM};
-pub use hero::{GameCommand, Hero};
+pub use env::{Env, RewardConfig};
+pub use event::GameEvent;
+pub use game::{
+    DeathPolicy, Game, GameConfig, MAX_MAZE_ID, MAX_MINOTAURS_PER_ROOM, POWER_UPS_PER_ROOM,
+};
+pub use grid::Grid;
+pub use hero::{GameCommand, Hero, RunStats, UiOptions};
 pub use maze::Maze;
 pub use minotaur::Minotaur;
+pub use npc::{Npc, NpcStrategy};
+pub use observation::{Cell, Observation, SeenHero, SeenMinotaur};
 pub use power_up::PowerUp;
-pub use utils::{GameColors, PlayerId, MAX_USERNAME_LEN};
+pub use theme::Theme;
+pub use tile::{Tile, TileMap};
+pub use time_trial::{daily_seed, GhostSample, GhostTarget, RunRecord};
+pub use utils::{to_player_name, GameColors, PlayerId, Rgba, MAX_USERNAME_LEN};
 pub use view::View;
 
 pub type Position = (usize, usize);
@@ -1,19 +1,128 @@
 use crate::{
-    direction::Direction, game::POWER_UPS_PER_ROOM, minotaur::Minotaur, utils::convert_rgb_to_rgba,
-    Entity, IntoDirection, Position, View, MAX_MAZE_ID,
+    direction::Direction, game::POWER_UPS_PER_ROOM, minotaur::Minotaur, Entity, Grid,
+    IntoDirection, Position, Rgba, Theme, View, MAX_MAZE_ID,
 };
 use anyhow::Result as AppResult;
-use image::{Rgb, Rgba, RgbaImage};
+#[cfg(feature = "image")]
+use image::RgbaImage;
 use itertools::Itertools;
-use knossos::maze::{self, GrowingTree, Method};
 use rand::{
-    seq::{IndexedRandom, IteratorRandom},
+    seq::{IndexedRandom, IteratorRandom, SliceRandom},
     RngExt, SeedableRng,
 };
 use rand_chacha::ChaCha8Rng;
 use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, PoisonError, RwLock};
+use uuid::Builder;
 
-#[derive(Debug, Clone, PartialEq)]
+type VisibilityKey = (Position, Direction, View);
+
+/// Positions visible from a position, direction and view. They only depend
+/// on the walls, so clones of a maze share the cache.
+#[derive(Debug, Clone, Default)]
+struct VisibilityCache(Arc<RwLock<HashMap<VisibilityKey, HashSet<Position>>>>);
+
+impl VisibilityCache {
+    fn get(&self, key: &VisibilityKey) -> Option<HashSet<Position>> {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(key)
+            .cloned()
+    }
+
+    fn insert(&self, key: VisibilityKey, visible_positions: HashSet<Position>) {
+        self.0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key, visible_positions);
+    }
+
+    fn len(&self) -> usize {
+        self.0.read().unwrap_or_else(PoisonError::into_inner).len()
+    }
+}
+
+/// Carves a perfect maze of `width`x`height` cells with the growing tree
+/// algorithm, growing from the newest cell 75% of the time and from a random
+/// one otherwise. Returns whether each position is a wall, walls and cells
+/// being `wall_size` and `passage_size` positions wide.
+fn carve(
+    width: usize,
+    height: usize,
+    wall_size: usize,
+    passage_size: usize,
+    rng: &mut ChaCha8Rng,
+) -> Grid<bool> {
+    let step = wall_size + passage_size;
+    let mut walls = Grid::new(
+        (step * width + wall_size) as u32,
+        (step * height + wall_size) as u32,
+        true,
+    );
+    // Opens the cells from `a` to `b`, and the wall between them.
+    let mut open = |(ax, ay): (usize, usize), (bx, by): (usize, usize)| {
+        for y in ay.min(by) * step + wall_size..ay.max(by) * step + step {
+            for x in ax.min(bx) * step + wall_size..ax.max(bx) * step + step {
+                walls.set(x as u32, y as u32, false);
+            }
+        }
+    };
+
+    let mut visited = Grid::new(width as u32, height as u32, false);
+    let start = (rng.random_range(0..width), rng.random_range(0..height));
+    visited.set(start.0 as u32, start.1 as u32, true);
+    open(start, start);
+    let mut cells = vec![start];
+    let mut directions = [(0, -1), (0, 1), (-1, 0), (1, 0)];
+    while !cells.is_empty() {
+        let index = if rng.random::<f32>() < 0.75 {
+            cells.len() - 1
+        } else {
+            rng.random_range(0..cells.len())
+        };
+        let (x, y) = cells[index];
+
+        directions.shuffle(rng);
+        let next = directions.iter().find_map(|&(dx, dy)| {
+            let next = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+            (visited.get(next.0 as u32, next.1 as u32) == Some(&false)).then_some(next)
+        });
+        if let Some(next) = next {
+            visited.set(next.0 as u32, next.1 as u32, true);
+            open((x, y), next);
+            cells.push(next);
+        } else {
+            cells.remove(index);
+        }
+    }
+
+    walls
+}
+
+/// Walls and passages of a maze, which never change once it is built, so
+/// that clones of the maze share them.
+#[derive(Debug, Clone)]
+struct Layout {
+    /// Whether each position is a wall.
+    walls: Grid<bool>,
+    valid_positions: HashSet<Position>,
+    entrance: Vec<Position>,
+    exit: Vec<Position>,
+}
+
+impl Layout {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            walls: Grid::new(width, height, true),
+            valid_positions: HashSet::new(),
+            entrance: Vec::new(),
+            exit: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Maze {
     id: usize,
     random_seed: u64,
@@ -22,26 +131,26 @@ pub struct Maze {
     height: usize,
     wall_size: usize,
     passage_size: usize,
-    image: RgbaImage,
-    valid_positions: HashSet<Position>,
-    entrance: Vec<Position>,
-    exit: Vec<Position>,
+    layout: Arc<Layout>,
     pub power_up_positions: Vec<Position>,
-    visible_positions_cache: HashMap<(Position, Direction, View), HashSet<Position>>, // (x, y, direction, type) -> visible positions
-    success_rate: (usize, usize), //pass/attempted
+    shrine_position: Option<Position>,
+    visible_positions_cache: VisibilityCache,
+    pub(crate) success_rate: (usize, usize), //pass/attempted
 }
 
 impl Maze {
     const DEFAULT_WALL_SIZE: usize = 2;
     const DEFAULT_PASSAGE_SIZE: usize = 2;
     const MARGIN_SIZE: usize = 0;
+    const SHRINE_ROOM_INTERVAL: usize = 3;
 
+    /// Only called while building the maze, before its layout is shared.
     fn insert_valid_position(&mut self, position: Position) {
-        self.valid_positions.insert(position);
+        let layout = Arc::make_mut(&mut self.layout);
+        layout.valid_positions.insert(position);
 
         let (x, y) = position;
-        self.image
-            .put_pixel(x as u32, y as u32, Self::background_color());
+        layout.walls.set(x as u32, y as u32, false);
     }
 
     fn build_entrance(&mut self) {
@@ -49,10 +158,10 @@ impl Maze {
 
         let entrance_y = rng.random_range(
             Self::MARGIN_SIZE + self.wall_size
-                ..self.image.height() as usize - Self::MARGIN_SIZE - self.wall_size - 1,
+                ..self.layout.walls.height() as usize - Self::MARGIN_SIZE - self.wall_size - 1,
         ) / 2
             * 2;
-        self.entrance = {
+        let entrance = {
             let starting_x = if self.id == 0 {
                 Self::MARGIN_SIZE + self.wall_size
             } else {
@@ -74,6 +183,7 @@ impl Maze {
 
             vec![(starting_x, entrance_y), (starting_x, entrance_y + 1)]
         };
+        Arc::make_mut(&mut self.layout).entrance = entrance;
     }
 
     fn build_exit(&mut self) {
@@ -81,11 +191,11 @@ impl Maze {
 
         let exit_y = rng.random_range(
             Self::MARGIN_SIZE + self.wall_size
-                ..self.image.height() as usize - Self::MARGIN_SIZE - self.wall_size - 1,
+                ..self.layout.walls.height() as usize - Self::MARGIN_SIZE - self.wall_size - 1,
         ) / 2
             * 2;
-        self.exit = {
-            let max_x = self.image.width() as usize - Self::MARGIN_SIZE - 1;
+        let exit = {
+            let max_x = self.layout.walls.width() as usize - Self::MARGIN_SIZE - 1;
             let mut x = max_x;
 
             loop {
@@ -100,6 +210,7 @@ impl Maze {
 
             vec![(max_x, exit_y), (max_x, exit_y + 1)]
         };
+        Arc::make_mut(&mut self.layout).exit = exit;
     }
 
     fn build_extra_rooms(&mut self) {
@@ -113,11 +224,14 @@ impl Maze {
 
             let room_x = rng.random_range(
                 Self::MARGIN_SIZE + self.wall_size
-                    ..self.image.width() as usize - room_width - Self::MARGIN_SIZE - self.wall_size,
+                    ..self.layout.walls.width() as usize
+                        - room_width
+                        - Self::MARGIN_SIZE
+                        - self.wall_size,
             );
             let room_y = rng.random_range(
                 Self::MARGIN_SIZE + self.wall_size
-                    ..self.image.height() as usize
+                    ..self.layout.walls.height() as usize
                         - room_height
                         - Self::MARGIN_SIZE
                         - self.wall_size,
@@ -135,39 +249,71 @@ impl Maze {
         }
     }
 
-    fn random_valid_position(&self) -> Position {
-        self.valid_positions
+    // Positions are sorted before sampling so that the same seed always
+    // yields the same choice, regardless of the HashSet iteration order.
+    fn random_valid_position(&mut self) -> Position {
+        self.layout
+            .valid_positions
             .iter()
-            .choose(&mut rand::rng())
+            .sorted()
+            .choose(&mut self.rng)
             .copied()
             .unwrap()
     }
 
     fn set_power_ups_position(&mut self, amount: usize) {
         self.power_up_positions = self
+            .layout
             .valid_positions
             .iter()
             .filter(|&&position| {
-                self.entrance
+                self.layout
+                    .entrance
                     .iter()
                     .all(|entrance| entrance.distance(position) > 6.0)
-                    && self.exit.iter().all(|exit| exit.distance(position) > 6.0)
+                    && self
+                        .layout
+                        .exit
+                        .iter()
+                        .all(|exit| exit.distance(position) > 6.0)
             })
-            .sample(&mut rand::rng(), amount)
+            .sorted()
+            .sample(&mut self.rng, amount)
             .into_iter()
             .copied()
             .collect_vec();
     }
 
-    fn color(id: usize) -> Rgba<u8> {
+    // Every few rooms a shrine is placed in the middle of the maze.
+    fn set_shrine_position(&mut self) {
+        if self.id % Self::SHRINE_ROOM_INTERVAL != Self::SHRINE_ROOM_INTERVAL - 1 {
+            return;
+        }
+
+        let center = (
+            self.layout.walls.width() as usize / 2,
+            self.layout.walls.height() as usize / 2,
+        );
+        self.shrine_position = self
+            .layout
+            .valid_positions
+            .iter()
+            .filter(|position| !self.power_up_positions.contains(position))
+            .min_by_key(|&&position| (position.distance_squared(center), position))
+            .copied();
+    }
+
+    /// Color of the walls of room `id`, blended from the first to the last
+    /// room colors of the theme.
+    pub fn color(id: usize, theme: &Theme) -> Rgba<u8> {
         let a = (id.min(MAX_MAZE_ID) as f64) / MAX_MAZE_ID as f64;
-        // red = Rgba([208, 28, 28, 125]);
-        // whiteblueish = Rgba([210, 240, 255, 125]);
+        let Rgba([fr, fg, fb, _]) = theme.first_walls;
+        let Rgba([lr, lg, lb, _]) = theme.last_walls;
 
         Rgba([
-            (a * 208.0 + (1.0 - a) * 210.0) as u8,
-            (a * 28.0 + (1.0 - a) * 240.0) as u8,
-            (a * 28.0 + (1.0 - a) * 255.0) as u8,
+            (a * lr as f64 + (1.0 - a) * fr as f64) as u8,
+            (a * lg as f64 + (1.0 - a) * fg as f64) as u8,
+            (a * lb as f64 + (1.0 - a) * fb as f64) as u8,
             125,
         ])
     }
@@ -192,8 +338,9 @@ impl Maze {
         self
     }
 
-    /// Sets a maze rng and returns itself
+    /// Sets a maze random seed and returns itself
     pub fn random_seed(mut self, random_seed: u64) -> Self {
+        self.random_seed = random_seed;
         self.rng = ChaCha8Rng::seed_from_u64(random_seed);
         self
     }
@@ -225,12 +372,10 @@ impl Maze {
             height: 0,
             wall_size: Self::DEFAULT_WALL_SIZE,
             passage_size: Self::DEFAULT_PASSAGE_SIZE,
-            image: RgbaImage::new(0, 0),
-            valid_positions: HashSet::new(),
-            entrance: Vec::new(),
-            exit: Vec::new(),
+            layout: Arc::new(Layout::new(0, 0)),
             power_up_positions: Vec::new(),
-            visible_positions_cache: HashMap::new(),
+            shrine_position: None,
+            visible_positions_cache: VisibilityCache::default(),
             success_rate: (0, 0),
         }
     }
@@ -248,38 +393,27 @@ impl Maze {
                 .random_range(4 + 2 * (self.id / 4)..=(6 + 2 * (self.id / 2)).min(20));
         }
 
-        let Rgba([r, g, b, _]) = Self::color(self.id);
-        let Rgba([br, bg, bb, _]) = Self::background_color();
-
-        let knossos_maze = maze::OrthogonalMazeBuilder::new()
-            .width(self.width)
-            .height(self.height)
-            .algorithm(Box::new(GrowingTree::new(Method::Newest75Random25)))
-            .seed(Some(self.random_seed))
-            .build();
-
-        let maze_image_wrapper = knossos_maze.format(
-            maze::Image::new()
-                .wall(self.wall_size)
-                .passage(self.passage_size)
-                .margin(Self::MARGIN_SIZE)
-                .background(knossos::Color::RGB(br, bg, bb))
-                .foreground(knossos::Color::RGB(r, g, b)),
+        let walls = carve(
+            self.width,
+            self.height,
+            self.wall_size,
+            self.passage_size,
+            &mut ChaCha8Rng::seed_from_u64(self.random_seed),
         );
 
-        self.image = convert_rgb_to_rgba(&maze_image_wrapper.into_inner(), Rgb([0; 3]));
-
-        self.valid_positions = self
-            .image
-            .enumerate_pixels()
-            .filter(|(_, _, pixel)| pixel[3] == 0)
-            .map(|(x, y, _)| (x as usize, y as usize))
-            .collect();
+        let (width, height) = walls.dimensions();
+        self.layout = Arc::new(Layout::new(width, height));
+        for ((x, y), &wall) in walls.iter() {
+            if !wall {
+                self.insert_valid_position((x as usize, y as usize));
+            }
+        }
 
         self.build_entrance();
         self.build_exit();
         self.build_extra_rooms();
         self.set_power_ups_position(POWER_UPS_PER_ROOM);
+        self.set_shrine_position();
 
         Ok(self)
     }
@@ -293,26 +427,27 @@ impl Maze {
         let speed = (self.id as u64 / 3).min(6);
         let aggression = (0.5 + 0.1 * (self.id / 2) as f64).min(1.0);
         let vision = (4 + self.id / 3).min(7);
-        let minotaur = Minotaur::new(name, self.id, position, speed, vision, aggression);
+        let id = Builder::from_random_bytes(self.rng.random()).into_uuid();
+        let minotaur = Minotaur::new(id, name, self.id, position, speed, vision, aggression);
         self.get_and_cache_visible_positions(position, minotaur.direction(), minotaur.view());
 
         minotaur
     }
 
     pub fn get_and_cache_visible_positions(
-        &mut self,
+        &self,
         position: Position,
         direction: Direction,
         view: View,
     ) -> HashSet<Position> {
         let cache_key = (position, direction, view);
         if let Some(visible_positions) = self.visible_positions_cache.get(&cache_key) {
-            return visible_positions.clone();
+            return visible_positions;
         }
 
         if view == View::Full {
             let mut visible_positions = HashSet::new();
-            for &(x, y) in self.valid_positions.iter() {
+            for &(x, y) in self.layout.valid_positions.iter() {
                 visible_positions.insert((x, y));
             }
 
@@ -325,11 +460,11 @@ impl Maze {
         let view_radius = view.radius();
 
         let mut visible_positions = HashSet::new();
-        for dy in
-            y.saturating_sub(view_radius)..=(y + view_radius).min(self.image().height() as usize)
+        for dy in y.saturating_sub(view_radius)
+            ..=(y + view_radius).min(self.layout.walls.height() as usize)
         {
-            for dx in
-                x.saturating_sub(view_radius)..=(x + view_radius).min(self.image().width() as usize)
+            for dx in x.saturating_sub(view_radius)
+                ..=(x + view_radius).min(self.layout.walls.width() as usize)
             {
                 // Origin is always visible
                 if x == dx && y == dy {
@@ -442,7 +577,7 @@ impl Maze {
         visible_positions = visible_positions
             .iter()
             .filter(|(x, y)| {
-                *x < self.image().width() as usize && *y < self.image().height() as usize
+                *x < self.layout.walls.width() as usize && *y < self.layout.walls.height() as usize
             })
             .map(|(x, y)| (*x, *y))
             .collect();
@@ -497,47 +632,77 @@ impl Maze {
         self.visible_positions_cache
             .get(&cache_key)
             .expect("Visible positions should have been cached")
-            .clone()
     }
 
-    pub fn image(&self) -> &RgbaImage {
-        &self.image
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.layout.walls.dimensions()
+    }
+
+    pub fn walls(&self) -> &Grid<bool> {
+        &self.layout.walls
+    }
+
+    /// The walls in the default color of the room, on a transparent floor.
+    #[cfg(feature = "image")]
+    pub fn image(&self) -> RgbaImage {
+        let Rgba([r, g, b, _]) = Self::color(self.id, &Theme::default());
+        let (width, height) = self.dimensions();
+        RgbaImage::from_fn(width, height, |x, y| {
+            if self.layout.walls.get(x, y) == Some(&true) {
+                Rgba([r, g, b, 255])
+            } else {
+                Self::background_color()
+            }
+        })
     }
 
+    #[cfg(feature = "image")]
     pub fn save_image(&self, name: &str) -> AppResult<()> {
-        self.image.save(name)?;
+        self.image().save(name)?;
         Ok(())
     }
 
     pub fn is_valid_position(&self, position: Position) -> bool {
-        self.valid_positions.contains(&position)
+        self.layout.valid_positions.contains(&position)
     }
 
     pub fn is_valid_minotaur_position(&self, position: Position) -> bool {
         let entrances = self.entrance_positions();
-        self.valid_positions.contains(&position)
+        self.layout.valid_positions.contains(&position)
             && entrances.iter().all(|p| p.distance(position) > 6.0)
     }
 
     pub fn is_entrance_position(&self, position: Position) -> bool {
-        self.entrance.contains(&position)
+        self.layout.entrance.contains(&position)
     }
 
     pub fn is_exit_position(&self, position: Position) -> bool {
-        self.exit.contains(&position)
+        self.layout.exit.contains(&position)
     }
 
     pub fn entrance_positions(&self) -> &Vec<Position> {
-        &self.entrance
+        &self.layout.entrance
     }
 
     pub fn exit_positions(&self) -> &Vec<Position> {
-        &self.exit
+        &self.layout.exit
+    }
+
+    pub fn visibility_cache_len(&self) -> usize {
+        self.visible_positions_cache.len()
     }
 
-    pub fn hero_starting_position(&self) -> Position {
-        let rng = &mut rand::rng();
-        *self.entrance.choose(rng).unwrap()
+    pub fn shrine_position(&self) -> Option<Position> {
+        self.shrine_position
+    }
+
+    pub fn hero_starting_position(&mut self) -> Position {
+        *self.layout.entrance.choose(&mut self.rng).unwrap()
+    }
+
+    /// The generator of the room, seeded with it.
+    pub(crate) fn rng(&mut self) -> &mut ChaCha8Rng {
+        &mut self.rng
     }
 
     pub fn increase_attempted(&mut self) {
@@ -602,15 +767,57 @@ fn bresenham_line(from: (i32, i32), to: (i32, i32)) -> Vec<Position> {
 
 #[cfg(test)]
 mod tests {
-    use super::Maze;
-    use crate::{game::MAX_MAZE_ID, AppResult};
+    use super::carve;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_carve() {
+        let walls = carve(5, 3, 2, 2, &mut ChaCha8Rng::seed_from_u64(7));
+        assert_eq!(walls.dimensions(), (22, 14));
+        // The border is all walls.
+        assert!(walls
+            .iter()
+            .filter(|((x, y), _)| *x < 2 || *y < 2 || *x >= 20 || *y >= 12)
+            .all(|(_, &wall)| wall));
+
+        // Every passage can be reached from any other.
+        let floor = walls
+            .iter()
+            .filter(|(_, &wall)| !wall)
+            .map(|(position, _)| position)
+            .collect::<Vec<_>>();
+        let mut reached = vec![floor[0]];
+        let mut frontier = vec![floor[0]];
+        while let Some((x, y)) = frontier.pop() {
+            for next in [
+                (x + 1, y),
+                (x.wrapping_sub(1), y),
+                (x, y + 1),
+                (x, y.wrapping_sub(1)),
+            ] {
+                if walls.get(next.0, next.1) == Some(&false) && !reached.contains(&next) {
+                    reached.push(next);
+                    frontier.push(next);
+                }
+            }
+        }
+        assert_eq!(reached.len(), floor.len());
+    }
 
     #[test]
-    fn test_random_mazes_image() -> AppResult<()> {
+    #[cfg(feature = "image")]
+    fn test_random_mazes_image() -> anyhow::Result<()> {
+        use super::Maze;
+        use crate::game::MAX_MAZE_ID;
+
+        let images_dir = std::env::temp_dir().join("asterion_images");
+        std::fs::create_dir_all(&images_dir)?;
+
         for id in 0..MAX_MAZE_ID {
-            let maze = Maze::new(id);
-            let name = format!("images/random_{}.png", id);
-            maze.save_image(&name)?;
+            let maze = Maze::new(id).build()?;
+            let name = images_dir.join(format!("random_{}.png", id));
+            maze.save_image(&name.to_string_lossy())?;
         }
 
         Ok(())
@@ -1,9 +1,9 @@
-use crate::{entity::Entity, Direction, Hero, IntoDirection, Maze, PlayerId, Position};
+use crate::{clock, entity::Entity, Direction, Hero, IntoDirection, Maze, PlayerId, Position};
 use itertools::Itertools;
 use rand::{seq::IteratorRandom, RngExt};
 use std::time::Instant;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Minotaur {
     id: PlayerId,
     name: String,
@@ -29,6 +29,7 @@ impl Minotaur {
     }
 
     pub fn new(
+        id: PlayerId,
         name: String,
         maze_id: usize,
         position: Position,
@@ -37,7 +38,7 @@ impl Minotaur {
         aggression: f64,
     ) -> Self {
         Self {
-            id: PlayerId::new_v4(),
+            id,
             name,
             chasing: None,
             maze_id,
@@ -46,7 +47,7 @@ impl Minotaur {
             speed,
             vision,
             aggression,
-            last_update_time: Instant::now(),
+            last_update_time: clock::now(),
             kills: 0,
         }
     }
@@ -60,11 +61,10 @@ impl Minotaur {
             self.chasing = Some(hero_id);
         }
 
-        if self.last_update_time.elapsed() < self.movement_recovery_duration() {
+        if clock::elapsed(self.last_update_time) < self.movement_recovery_duration() {
             return;
         }
 
-        let rng = &mut rand::rng();
         let (x, y) = self.position;
 
         let mut available_directions = [
@@ -95,20 +95,23 @@ impl Minotaur {
                             (y as isize + d.as_offset().1).max(0) as usize,
                         );
                         new_position.distance(hero.position()) < current_distance
-                    }).copied()
+                    })
+                    .copied()
                     .collect_vec();
             }
         }
 
-        if !available_directions.is_empty() && rng.random_bool(self.aggression) {
+        // Moves are drawn from the room's generator, so that they follow its
+        // seed.
+        if !available_directions.is_empty() && maze.rng().random_bool(self.aggression) {
             // Pick a random available direction
-            let direction = available_directions.iter().choose(rng).unwrap();
+            let direction = available_directions.iter().choose(maze.rng()).unwrap();
             let new_position = (
                 (x as isize + direction.as_offset().0).max(0) as usize,
                 (y as isize + direction.as_offset().1).max(0) as usize,
             );
             self.position = new_position;
-            self.last_update_time = Instant::now();
+            self.last_update_time = clock::now();
         }
     }
 
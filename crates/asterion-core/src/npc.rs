@@ -0,0 +1,196 @@
+use crate::{Cell, Direction, GameCommand, IntoDirection, Observation, Position};
+use std::collections::{HashMap, HashSet, VecDeque};
+use strum_macros::Display;
+
+const CARDINAL_DIRECTIONS: [Direction; 4] = [
+    Direction::North,
+    Direction::East,
+    Direction::South,
+    Direction::West,
+];
+
+#[derive(Debug, Clone, Copy, Display, PartialEq)]
+pub enum NpcStrategy {
+    /// Keeps a hand on the left wall.
+    WallFollower,
+    /// Heads to the closest unvisited cell next to the unexplored part of its
+    /// memory, or straight to the exit once it has seen it.
+    Frontier,
+}
+
+/// The mind of a hero played by the server. It only knows what the hero has
+/// seen, and plays through the same commands as everybody else.
+#[derive(Debug, Clone)]
+pub struct Npc {
+    strategy: NpcStrategy,
+    maze_id: usize,
+    heading: Direction,
+    known: HashMap<Position, Cell>,
+    visited: HashSet<Position>,
+}
+
+impl Npc {
+    pub fn new(strategy: NpcStrategy) -> Self {
+        Self {
+            strategy,
+            maze_id: 0,
+            heading: Direction::East,
+            known: HashMap::new(),
+            visited: HashSet::new(),
+        }
+    }
+
+    pub fn strategy(&self) -> NpcStrategy {
+        self.strategy
+    }
+
+    /// Entrances lead back to the previous room, which is never the goal.
+    fn is_open(&self, position: Position) -> bool {
+        matches!(
+            self.known.get(&position),
+            Some(Cell::Floor) | Some(Cell::Exit)
+        )
+    }
+
+    fn neighbours(position: Position) -> impl Iterator<Item = (Direction, Position)> {
+        CARDINAL_DIRECTIONS
+            .into_iter()
+            .map(move |direction| (direction, position.into_direction(&direction)))
+            .filter(move |(_, next)| *next != position)
+    }
+
+    fn wall_follower_direction(&mut self, position: Position) -> Option<Direction> {
+        let left = self
+            .heading
+            .rotate_counter_clockwise()
+            .rotate_counter_clockwise();
+        let direction = [left, self.heading, left.opposite(), self.heading.opposite()]
+            .into_iter()
+            .find(|direction| self.is_open(position.into_direction(direction)))?;
+        self.heading = direction;
+        Some(direction)
+    }
+
+    fn frontier_direction(&self, position: Position) -> Option<Direction> {
+        // Some cells stay out of sight of the hero standing next to them, so
+        // each one is only worth a single visit.
+        let is_frontier = |position: Position| {
+            !self.visited.contains(&position)
+                && Self::neighbours(position).any(|(_, next)| !self.known.contains_key(&next))
+        };
+        let exit_known = self.known.values().any(|cell| *cell == Cell::Exit);
+
+        // Breadth first search, remembering the first step of every path.
+        let mut visited = HashSet::from([position]);
+        let mut queue = VecDeque::new();
+        for (direction, next) in Self::neighbours(position) {
+            if self.is_open(next) && visited.insert(next) {
+                queue.push_back((next, direction));
+            }
+        }
+        while let Some((current, first_step)) = queue.pop_front() {
+            let is_goal = if exit_known {
+                self.known.get(&current) == Some(&Cell::Exit)
+            } else {
+                is_frontier(current)
+            };
+            if is_goal {
+                return Some(first_step);
+            }
+            for (_, next) in Self::neighbours(current) {
+                if self.is_open(next) && visited.insert(next) {
+                    queue.push_back((next, first_step));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// What to do next, if anything.
+    pub fn next_command(&mut self, observation: &Observation) -> Option<GameCommand> {
+        if observation.maze_id != self.maze_id {
+            self.maze_id = observation.maze_id;
+            self.known.clear();
+            self.visited.clear();
+        }
+        self.visited.insert(observation.position);
+        self.known.extend(observation.remembered.iter().copied());
+        self.known.extend(observation.visible.iter().copied());
+
+        if !observation.can_move {
+            return None;
+        }
+
+        let direction = match self.strategy {
+            NpcStrategy::WallFollower => self.wall_follower_direction(observation.position),
+            NpcStrategy::Frontier => self
+                .frontier_direction(observation.position)
+                .or_else(|| self.wall_follower_direction(observation.position)),
+        }?;
+
+        Some(GameCommand::Move { direction })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Npc, NpcStrategy};
+    use crate::{Cell, Direction, GameCommand, Observation, PlayerId};
+    use std::time::Duration;
+
+    /// A corridor going east from the hero, with the exit at its end.
+    fn corridor_observation(position: (usize, usize)) -> Observation {
+        let visible = (0..5)
+            .flat_map(|x| {
+                let cell = if x == 4 { Cell::Exit } else { Cell::Floor };
+                [((x, 0), Cell::Wall), ((x, 1), cell), ((x, 2), Cell::Wall)]
+            })
+            .collect();
+        Observation {
+            hero_id: PlayerId::new_v4(),
+            state: "InMaze".to_string(),
+            maze_id: 0,
+            position,
+            direction: Direction::West,
+            can_move: true,
+            vision: 1,
+            speed: 4,
+            memory: 0,
+            elapsed: Duration::ZERO,
+            deaths: 0,
+            victories: 0,
+            visible,
+            remembered: vec![],
+            minotaurs: vec![],
+            heros: vec![],
+            power_ups: vec![],
+            shrine: None,
+        }
+    }
+
+    #[test]
+    fn test_npc_heads_for_the_exit() {
+        for strategy in [NpcStrategy::WallFollower, NpcStrategy::Frontier] {
+            let mut npc = Npc::new(strategy);
+            let command = npc.next_command(&corridor_observation((1, 1)));
+            assert!(
+                matches!(
+                    command,
+                    Some(GameCommand::Move {
+                        direction: Direction::East
+                    })
+                ),
+                "{strategy} went {command:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_npc_waits_to_recover() {
+        let mut npc = Npc::new(NpcStrategy::Frontier);
+        let mut observation = corridor_observation((1, 1));
+        observation.can_move = false;
+        assert!(npc.next_command(&observation).is_none());
+    }
+}
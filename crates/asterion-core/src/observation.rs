@@ -0,0 +1,55 @@
+use crate::{Direction, PlayerId, Position};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Cell {
+    Wall,
+    Floor,
+    Entrance,
+    Exit,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SeenMinotaur {
+    pub id: PlayerId,
+    pub position: Position,
+    pub direction: Direction,
+    pub chasing: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SeenHero {
+    pub id: PlayerId,
+    pub name: String,
+    pub position: Position,
+}
+
+/// What a hero knows about the game, i.e. what a player would see on their
+/// screen, for programs playing the game.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Observation {
+    pub hero_id: PlayerId,
+    pub state: String,
+    pub maze_id: usize,
+    pub position: Position,
+    pub direction: Direction,
+    pub can_move: bool,
+    pub vision: usize,
+    pub speed: u64,
+    pub memory: u64,
+    pub elapsed: Duration,
+    pub deaths: usize,
+    pub victories: usize,
+    /// Cells currently in view, sorted by position.
+    pub visible: Vec<(Position, Cell)>,
+    /// Cells seen recently and still remembered, sorted by position.
+    pub remembered: Vec<(Position, Cell)>,
+    pub minotaurs: Vec<SeenMinotaur>,
+    pub heros: Vec<SeenHero>,
+    pub power_ups: Vec<Position>,
+    pub shrine: Option<Position>,
+}
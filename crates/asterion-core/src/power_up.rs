@@ -1,6 +1,7 @@
 use strum_macros::{Display, EnumIter};
 
 #[derive(Debug, Clone, Copy, Display, Hash, PartialEq, Eq, EnumIter)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PowerUp {
     Speed,
     Vision,
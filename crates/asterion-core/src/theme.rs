@@ -0,0 +1,154 @@
+use crate::{hero::UiOptions, GameColors, Rgba};
+
+/// Colors used to draw the game and its UI. Pure black is reserved for the
+/// empty pixels of the room images, so palettes should avoid it for anything
+/// but the background.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub background: Rgba<u8>,
+    pub text: Rgba<u8>,
+    pub hero: Rgba<u8>,
+    pub other_hero: Rgba<u8>,
+    pub minotaur: Rgba<u8>,
+    pub chasing_minotaur: Rgba<u8>,
+    pub power_up: Rgba<u8>,
+    pub ghost: Rgba<u8>,
+    pub shrine: Rgba<u8>,
+    pub explored: Rgba<u8>,
+    pub entrance: Rgba<u8>,
+    pub exit: Rgba<u8>,
+    /// Walls of the first and of the last room, the ones in between are
+    /// blended from the two. Their alpha is ignored.
+    pub first_walls: Rgba<u8>,
+    pub last_walls: Rgba<u8>,
+    /// Splits ahead and behind the best ones.
+    pub ahead: Rgba<u8>,
+    pub behind: Rgba<u8>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            name: "Dark".to_string(),
+            background: Rgba([0, 0, 0, 255]),
+            text: Rgba([255, 255, 255, 255]),
+            hero: GameColors::HERO,
+            other_hero: GameColors::OTHER_HERO,
+            minotaur: GameColors::MINOTAUR,
+            chasing_minotaur: GameColors::CHASING_MINOTAUR,
+            power_up: GameColors::POWER_UP,
+            ghost: GameColors::GHOST,
+            shrine: GameColors::SHRINE,
+            explored: GameColors::EXPLORED,
+            entrance: GameColors::ENTRANCE,
+            exit: GameColors::EXIT,
+            first_walls: Rgba([210, 240, 255, 255]),
+            last_walls: Rgba([208, 28, 28, 255]),
+            ahead: Rgba([80, 255, 80, 255]),
+            behind: Rgba([255, 80, 80, 255]),
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            name: "Light".to_string(),
+            background: Rgba([250, 250, 245, 255]),
+            text: Rgba([20, 20, 20, 255]),
+            hero: Rgba([0, 0, 200, 255]),
+            other_hero: Rgba([0, 140, 0, 255]),
+            minotaur: Rgba([180, 140, 0, 255]),
+            chasing_minotaur: Rgba([220, 0, 0, 255]),
+            power_up: Rgba([200, 0, 160, 255]),
+            ghost: Rgba([110, 60, 200, 160]),
+            shrine: Rgba([90, 90, 90, 255]),
+            explored: Rgba([190, 190, 190, 255]),
+            entrance: Rgba([0, 150, 150, 255]),
+            exit: Rgba([220, 100, 0, 255]),
+            first_walls: Rgba([40, 70, 120, 255]),
+            last_walls: Rgba([150, 20, 20, 255]),
+            ahead: Rgba([0, 140, 0, 255]),
+            behind: Rgba([200, 0, 0, 255]),
+        }
+    }
+
+    /// Bright walls on black and the Okabe-Ito palette, which stays distinct
+    /// for the common forms of color blindness.
+    pub fn high_contrast() -> Self {
+        Self {
+            name: "HighContrast".to_string(),
+            background: Rgba([0, 0, 0, 255]),
+            text: Rgba([255, 255, 255, 255]),
+            hero: Rgba([0, 114, 178, 255]),
+            other_hero: Rgba([0, 158, 115, 255]),
+            minotaur: Rgba([240, 228, 66, 255]),
+            chasing_minotaur: Rgba([213, 94, 0, 255]),
+            power_up: Rgba([204, 121, 167, 255]),
+            ghost: Rgba([255, 255, 255, 160]),
+            shrine: Rgba([255, 255, 255, 255]),
+            explored: Rgba([128, 128, 128, 255]),
+            entrance: Rgba([86, 180, 233, 255]),
+            exit: Rgba([230, 159, 0, 255]),
+            first_walls: Rgba([255, 255, 255, 255]),
+            last_walls: Rgba([255, 255, 255, 255]),
+            ahead: Rgba([86, 180, 233, 255]),
+            behind: Rgba([213, 94, 0, 255]),
+        }
+    }
+
+    /// The built-in themes, in the order of [`UiOptions`].
+    pub fn built_in() -> Vec<Self> {
+        vec![Self::dark(), Self::light(), Self::high_contrast()]
+    }
+
+    /// Index of the theme picked by `ui_options` among the built-in themes
+    /// followed by the custom ones.
+    pub fn index(ui_options: UiOptions) -> usize {
+        match ui_options {
+            UiOptions::Dark => 0,
+            UiOptions::Light => 1,
+            UiOptions::HighContrast => 2,
+            UiOptions::Custom { index } => 3 + index,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Theme;
+    use crate::{hero::UiOptions, Game, GameConfig};
+    use anyhow::Result as AppResult;
+
+    #[test]
+    fn test_cycle_themes() -> AppResult<()> {
+        let custom = Theme {
+            name: "Custom".to_string(),
+            ..Theme::light()
+        };
+        let game = Game::with_config(GameConfig {
+            seed: Some(0),
+            themes: vec![custom.clone()],
+            ..Default::default()
+        })?;
+
+        let mut ui_options = UiOptions::Dark;
+        let mut names = vec![];
+        for _ in 0..5 {
+            names.push(game.theme(ui_options).name.clone());
+            ui_options = ui_options.next(game.config().themes.len());
+        }
+        assert_eq!(names, ["Dark", "Light", "HighContrast", "Custom", "Dark"]);
+
+        // Custom themes removed from the config fall back to the default one.
+        assert_eq!(game.theme(UiOptions::Custom { index: 3 }), &Theme::dark());
+        assert_eq!(UiOptions::HighContrast.next(0), UiOptions::Dark);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use crate::Grid;
+
+/// What a hero sees at a position of their room, for frontends to draw in
+/// their own way. `visibility` goes from 0, about to be forgotten or at the
+/// edge of the view, to 255 right next to the hero.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Tile {
+    /// Never seen, or forgotten.
+    #[default]
+    Unknown,
+    Wall {
+        visibility: u8,
+    },
+    /// Floor in view.
+    Floor {
+        visibility: u8,
+    },
+    /// Floor seen recently, but out of view.
+    FoggedFloor,
+    /// The way back to the previous room, marked from the second room on.
+    /// Frontends can label it with the number of the room it leads to,
+    /// `maze_id` counting rooms from 1.
+    Entrance {
+        visibility: u8,
+    },
+    /// The way to the next room, numbered `maze_id + 2`.
+    Exit {
+        visibility: u8,
+    },
+    Hero,
+    OtherHero,
+    Minotaur,
+    ChasingMinotaur,
+    PowerUp,
+    Ghost,
+    Shrine,
+}
+
+/// The tiles of the part of a room shown by a camera.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TileMap {
+    maze_id: usize,
+    tiles: Grid<Tile>,
+}
+
+impl TileMap {
+    pub fn new(maze_id: usize, width: u32, height: u32) -> Self {
+        Self {
+            maze_id,
+            tiles: Grid::new(width, height, Tile::Unknown),
+        }
+    }
+
+    pub fn maze_id(&self) -> usize {
+        self.maze_id
+    }
+
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.tiles.dimensions()
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Option<Tile> {
+        self.tiles.get(x, y).copied()
+    }
+
+    /// Sets the tile at `(x, y)`, ignoring positions out of the map.
+    pub fn set(&mut self, x: u32, y: u32, tile: Tile) {
+        self.tiles.set(x, y, tile);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ((u32, u32), Tile)> + '_ {
+        self.tiles.iter().map(|(position, &tile)| (position, tile))
+    }
+}
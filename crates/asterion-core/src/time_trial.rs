@@ -0,0 +1,88 @@
+use crate::Position;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use strum_macros::Display;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// The seed shared by every time trial played on the same (UTC) day.
+pub fn daily_seed(now: SystemTime) -> u64 {
+    now.duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() / SECONDS_PER_DAY)
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Clone, Copy, Display, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GhostTarget {
+    Off,
+    PersonalBest,
+    Leader,
+}
+
+impl GhostTarget {
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Off => Self::PersonalBest,
+            Self::PersonalBest => Self::Leader,
+            Self::Leader => Self::Off,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GhostSample {
+    pub elapsed: Duration,
+    pub maze_id: usize,
+    pub position: Position,
+}
+
+/// A completed run, kept to be raced as a ghost.
+#[derive(Debug, Clone)]
+pub struct RunRecord {
+    pub name: String,
+    pub duration: Duration,
+    pub splits: Vec<Duration>,
+    pub path: Vec<GhostSample>,
+}
+
+impl RunRecord {
+    /// Where the ghost was after `elapsed` time from the start of the run.
+    pub fn ghost_at(&self, elapsed: Duration) -> Option<&GhostSample> {
+        let index = self
+            .path
+            .partition_point(|sample| sample.elapsed <= elapsed);
+        self.path.get(index.saturating_sub(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GhostSample, RunRecord};
+    use std::time::Duration;
+
+    #[test]
+    fn test_ghost_at() {
+        let run = RunRecord {
+            name: "ghost".to_string(),
+            duration: Duration::from_secs(3),
+            splits: vec![],
+            path: (0..3)
+                .map(|idx| GhostSample {
+                    elapsed: Duration::from_secs(idx),
+                    maze_id: 0,
+                    position: (idx as usize, 0),
+                })
+                .collect(),
+        };
+
+        assert_eq!(run.ghost_at(Duration::ZERO).unwrap().position, (0, 0));
+        assert_eq!(
+            run.ghost_at(Duration::from_millis(1500)).unwrap().position,
+            (1, 0)
+        );
+        assert_eq!(
+            run.ghost_at(Duration::from_secs(10)).unwrap().position,
+            (2, 0)
+        );
+    }
+}
@@ -1,11 +1,19 @@
-use image::{Pixel, Rgb, RgbImage, Rgba, RgbaImage};
 use rand::{rngs::ThreadRng, seq::IndexedRandom, RngExt};
 
 pub type PlayerId = uuid::Uuid;
 
+/// Colors are the pixels of the `image` crate with the `image` feature, and
+/// a plain struct of the same shape without it.
+#[cfg(feature = "image")]
+pub use image::Rgba;
+
+#[cfg(not(feature = "image"))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Rgba<T>(pub [T; 4]);
+
 #[inline]
 pub fn is_transparent(pixel: &Rgba<u8>, background: &Rgba<u8>) -> bool {
-    pixel[3] == 0 || pixel.to_rgb() == background.to_rgb()
+    pixel.0[3] == 0 || pixel.0[..3] == background.0[..3]
 }
 
 pub const MAX_USERNAME_LEN: usize = 12;
@@ -22,22 +30,17 @@ pub fn random_minotaur_name() -> String {
     MINOTAUR_NAMES.choose(&mut rand::rng()).unwrap().to_string()
 }
 
-pub fn convert_rgb_to_rgba(rgb_image: &RgbImage, background: Rgb<u8>) -> RgbaImage {
-    let (width, height) = rgb_image.dimensions();
-    let mut rgba_image = RgbaImage::new(width, height);
-
-    for (x, y, rgb_pixel) in rgb_image.enumerate_pixels() {
-        let alpha = if rgb_pixel.to_rgb() == background {
-            0
-        } else {
-            255
-        };
-
-        let Rgba([r, g, b, a]) = Rgba([rgb_pixel[0], rgb_pixel[1], rgb_pixel[2], alpha]);
-        rgba_image.put_pixel(x, y, Rgba([r, g, b, a]));
-    }
-
-    rgba_image
+/// Picks a name which is not `taken`, if any is left.
+pub fn random_npc_name(taken: impl Fn(&str) -> bool) -> String {
+    let rng = &mut rand::rng();
+    let free = NPC_NAMES
+        .iter()
+        .filter(|name| !taken(name))
+        .collect::<Vec<_>>();
+    free.choose(rng)
+        .copied()
+        .unwrap_or_else(|| NPC_NAMES.choose(rng).unwrap())
+        .to_string()
 }
 
 pub struct GameColors {}
@@ -48,6 +51,11 @@ impl GameColors {
     pub const MINOTAUR: Rgba<u8> = Rgba([225, 203, 3, 255]);
     pub const CHASING_MINOTAUR: Rgba<u8> = Rgba([255, 15, 0, 255]);
     pub const POWER_UP: Rgba<u8> = Rgba([255, 180, 244, 255]);
+    pub const GHOST: Rgba<u8> = Rgba([175, 135, 255, 160]);
+    pub const SHRINE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+    pub const EXPLORED: Rgba<u8> = Rgba([90, 90, 90, 255]);
+    pub const ENTRANCE: Rgba<u8> = Rgba([0, 200, 200, 255]);
+    pub const EXIT: Rgba<u8> = Rgba([255, 130, 0, 255]);
 }
 
 pub const MINOTAUR_NAMES: [&str; 7] = [
@@ -59,3 +67,13 @@ pub const MINOTAUR_NAMES: [&str; 7] = [
     "Πτερέλαος",
     "Τάφος",
 ];
+
+pub const NPC_NAMES: [&str; 7] = [
+    "Theseus",
+    "Ariadne",
+    "Daedalus",
+    "Icarus",
+    "Phaedra",
+    "Androgeos",
+    "Glaucus",
+];
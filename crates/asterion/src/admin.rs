@@ -0,0 +1,143 @@
+//! Operator console listening on a local Unix socket, e.g.
+//! `socat - UNIX-CONNECT:<path>`. Every line is a command and gets a text
+//! reply; access is limited to the owner of the socket directory.
+
+use crate::AppResult;
+use anyhow::anyhow;
+use std::fs::DirBuilder;
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::path::PathBuf;
+use std::str::FromStr;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{mpsc, oneshot};
+
+const HELP: &str = "\
+list                       list the players in the game
+kick <id|name>             disconnect a player
+ban <id|name|fingerprint>  ban a key and disconnect its player
+unban <id|name|fingerprint>
+broadcast <message>        show a message to every player
+reset <room>               rebuild a room, with a fresh layout unless seeded
+minotaurs <room> <count>   set the number of minotaurs in a room
+snapshot <id|name>         save what a player sees of their room as PNG";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AdminCommand {
+    List,
+    Kick { player: String },
+    Ban { player: String },
+    Unban { player: String },
+    Broadcast { message: String },
+    ResetRoom { maze_id: usize },
+    SetMinotaurs { maze_id: usize, count: usize },
+    Snapshot { player: String },
+}
+
+impl FromStr for AdminCommand {
+    type Err = anyhow::Error;
+
+    fn from_str(line: &str) -> AppResult<Self> {
+        let line = line.trim();
+        let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+        let args = args.trim();
+        let arg = || {
+            if args.is_empty() {
+                Err(anyhow!("Missing argument for {command}"))
+            } else {
+                Ok(args.to_string())
+            }
+        };
+
+        let command = match command {
+            "list" => Self::List,
+            "kick" => Self::Kick { player: arg()? },
+            "ban" => Self::Ban { player: arg()? },
+            "unban" => Self::Unban { player: arg()? },
+            "broadcast" => Self::Broadcast { message: arg()? },
+            "reset" => Self::ResetRoom {
+                maze_id: arg()?.parse()?,
+            },
+            "minotaurs" => {
+                let (maze_id, count) = args
+                    .split_once(' ')
+                    .ok_or(anyhow!("Usage: minotaurs <room> <count>"))?;
+                Self::SetMinotaurs {
+                    maze_id: maze_id.parse()?,
+                    count: count.trim().parse()?,
+                }
+            }
+            "snapshot" => Self::Snapshot { player: arg()? },
+            _ => return Err(anyhow!("Unknown command {command:?}, try 'help'")),
+        };
+        Ok(command)
+    }
+}
+
+/// A command for the game task, with the channel to send its reply on.
+pub type AdminRequest = (AdminCommand, oneshot::Sender<String>);
+
+async fn handle_connection(
+    stream: UnixStream,
+    sender: mpsc::Sender<AdminRequest>,
+) -> AppResult<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let reply = if line.trim() == "help" {
+            HELP.to_string()
+        } else {
+            match line.parse::<AdminCommand>() {
+                Ok(command) => {
+                    let (reply_sender, reply_receiver) = oneshot::channel();
+                    sender.send((command, reply_sender)).await?;
+                    reply_receiver.await?
+                }
+                Err(err) => err.to_string(),
+            }
+        };
+        writer.write_all(format!("{reply}\n").as_bytes()).await?;
+    }
+
+    Ok(())
+}
+
+/// Listens on `path`, in a directory only the owner can enter, so that the
+/// socket is never reachable by other users, not even before its own
+/// permissions are restricted.
+pub fn spawn(path: PathBuf, sender: mpsc::Sender<AdminRequest>) -> AppResult<()> {
+    let dir = path
+        .parent()
+        .ok_or_else(|| anyhow!("Invalid admin socket path {}", path.display()))?;
+    DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+    std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+    if path.exists() {
+        std::fs::remove_file(&path)?;
+    }
+    let listener = UnixListener::bind(&path)?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+    log::info!("Admin console listening on {}", path.display());
+
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let sender = sender.clone();
+                    tokio::spawn(async move {
+                        if let Err(err) = handle_connection(stream, sender).await {
+                            log::warn!("Admin connection error: {err}");
+                        }
+                    });
+                }
+                Err(err) => log::error!("Admin console error: {err}"),
+            }
+        }
+    });
+
+    Ok(())
+}
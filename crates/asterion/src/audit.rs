@@ -0,0 +1,67 @@
+//! Append-only log of every `GameEvent`, one JSON object per line.
+
+use crate::AppResult;
+use asterion_core::GameEvent;
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+
+#[derive(Serialize)]
+struct AuditRecord<'a> {
+    timestamp_ms: u128,
+    #[serde(flatten)]
+    event: &'a GameEvent,
+}
+
+/// Writes the records handed over by the game task, flushing whenever it
+/// catches up with them.
+fn write_records(mut writer: BufWriter<File>, mut records: mpsc::UnboundedReceiver<String>) {
+    while let Some(record) = records.blocking_recv() {
+        let mut result = writer.write_all(record.as_bytes());
+        if records.is_empty() {
+            result = result.and_then(|_| writer.flush());
+        }
+        if let Err(err) = result {
+            log::error!("Unable to write audit log: {err}");
+        }
+    }
+    let _ = writer.flush();
+}
+
+/// Serializes the events on the game task and leaves the disk to a blocking
+/// task of its own.
+pub struct AuditLog {
+    records: mpsc::UnboundedSender<String>,
+}
+
+impl AuditLog {
+    pub fn open(path: &Path) -> AppResult<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let (records, receiver) = mpsc::unbounded_channel();
+        let writer = BufWriter::new(file);
+        tokio::task::spawn_blocking(move || write_records(writer, receiver));
+        Ok(Self { records })
+    }
+
+    pub fn write(&mut self, events: &[GameEvent]) -> AppResult<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        let timestamp_ms = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+        let mut records = String::new();
+        for event in events {
+            records.push_str(&serde_json::to_string(&AuditRecord {
+                timestamp_ms,
+                event,
+            })?);
+            records.push('\n');
+        }
+        self.records.send(records)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+//! Line-delimited JSON protocol for programs playing the game, on a local TCP
+//! port.
+//!
+//! The client first sends `{"name": "<name>"}`, then receives an
+//! `{"observation": {...}}` line with what its hero sees every frame, and
+//! sends `GameCommand`s such as `{"Move": {"direction": "North"}}` or
+//! `"TurnClockwise"`, one per line. Problems are reported as
+//! `{"error": "..."}`. Commands go through the same rules as key presses,
+//! including the input rate limit; frames are dropped if the client does not
+//! keep up.
+
+use crate::input::InputRateLimiter;
+use crate::utils::sanitize_username;
+use crate::{AppResult, PlayerId};
+use asterion_core::{GameCommand, Observation};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::time::Instant;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::select;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+const FRAME_BUFFER: usize = 4;
+
+#[derive(Debug, Deserialize)]
+struct Hello {
+    name: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BotFrame {
+    Observation(Box<Observation>),
+    Error(String),
+}
+
+#[derive(Debug)]
+pub enum BotMessage {
+    Join {
+        id: PlayerId,
+        name: String,
+        frames: mpsc::Sender<BotFrame>,
+    },
+    Command {
+        id: PlayerId,
+        command: GameCommand,
+    },
+    Leave {
+        id: PlayerId,
+    },
+}
+
+async fn write_frame(writer: &mut OwnedWriteHalf, frame: &BotFrame) -> AppResult<()> {
+    let mut line = serde_json::to_vec(frame)?;
+    line.push(b'\n');
+    writer.write_all(&line).await?;
+    Ok(())
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    sender: mpsc::Sender<BotMessage>,
+    input_rate: u32,
+) -> AppResult<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    let hello = if let Some(line) = lines.next_line().await? {
+        line
+    } else {
+        return Ok(());
+    };
+    let name = match serde_json::from_str::<Hello>(&hello) {
+        Ok(hello) => sanitize_username(&hello.name),
+        Err(err) => {
+            let frame = BotFrame::Error(format!("Expected {{\"name\": ...}}: {err}"));
+            return write_frame(&mut writer, &frame).await;
+        }
+    };
+
+    let id = Uuid::new_v4();
+    let (frames, mut frame_receiver) = mpsc::channel(FRAME_BUFFER);
+    sender.send(BotMessage::Join { id, name, frames }).await?;
+
+    let mut input_rate_limiter = InputRateLimiter::new(input_rate);
+    let result: AppResult<()> = async {
+        loop {
+            select! {
+                frame = frame_receiver.recv() => {
+                    // The game dropped the bot: it was kicked or refused.
+                    let frame = if let Some(frame) = frame {
+                        frame
+                    } else {
+                        return Ok(());
+                    };
+                    write_frame(&mut writer, &frame).await?;
+                }
+
+                line = lines.next_line() => {
+                    let line = if let Some(line) = line? {
+                        line
+                    } else {
+                        return Ok(());
+                    };
+                    if !input_rate_limiter.allow(Instant::now()) {
+                        continue;
+                    }
+                    match serde_json::from_str::<GameCommand>(&line) {
+                        Ok(command) => sender.send(BotMessage::Command { id, command }).await?,
+                        Err(err) => {
+                            let frame = BotFrame::Error(format!("Invalid command: {err}"));
+                            write_frame(&mut writer, &frame).await?;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    .await;
+
+    let _ = sender.send(BotMessage::Leave { id }).await;
+    result
+}
+
+pub fn spawn(
+    addr: SocketAddr,
+    sender: mpsc::Sender<BotMessage>,
+    input_rate: u32,
+) -> AppResult<SocketAddr> {
+    let listener = std::net::TcpListener::bind(addr)?;
+    listener.set_nonblocking(true)?;
+    let listener = TcpListener::from_std(listener)?;
+    let local_addr = listener.local_addr()?;
+    log::info!("Listening for bots on {local_addr}");
+
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let sender = sender.clone();
+                    tokio::spawn(async move {
+                        if let Err(err) = handle_connection(stream, sender, input_rate).await {
+                            log::warn!("Bot connection error: {err}");
+                        }
+                    });
+                }
+                Err(err) => log::error!("Bot listener error: {err}"),
+            }
+        }
+    });
+
+    Ok(local_addr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{spawn, BotFrame, BotMessage};
+    use crate::AppResult;
+    use asterion_core::{Game, GameCommand};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpStream;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_bot_protocol() -> AppResult<()> {
+        let (sender, mut messages) = mpsc::channel(64);
+        let addr = spawn("127.0.0.1:0".parse()?, sender, 2)?;
+
+        // A client which does not introduce itself is turned away.
+        let mut stream = TcpStream::connect(addr).await?;
+        stream.write_all(b"\"TurnClockwise\"\n").await?;
+        let mut lines = BufReader::new(stream).lines();
+        let line = lines.next_line().await?.unwrap();
+        assert!(line.starts_with("{\"error\":\"Expected {\\\"name\\\": ...}"));
+        assert!(lines.next_line().await?.is_none());
+
+        let stream = TcpStream::connect(addr).await?;
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer.write_all(b"{\"name\": \"bot\\u001b[2J\"}\n").await?;
+        let Some(BotMessage::Join { id, name, frames }) = messages.recv().await else {
+            panic!("Expected the bot to join");
+        };
+        assert_eq!(name, "bot[2J");
+
+        // Observations of the game are forwarded one per line.
+        let mut game = Game::new()?;
+        game.add_player(id, &name);
+        frames
+            .send(BotFrame::Observation(Box::new(game.observe(id)?)))
+            .await?;
+        let line = lines.next_line().await?.unwrap();
+        assert!(line.starts_with("{\"observation\":{"));
+
+        writer.write_all(b"\"TurnClockwise\"\n").await?;
+        let Some(BotMessage::Command { command, .. }) = messages.recv().await else {
+            panic!("Expected a command");
+        };
+        assert!(matches!(command, GameCommand::TurnClockwise));
+
+        writer.write_all(b"\"Fly\"\n").await?;
+        let line = lines.next_line().await?.unwrap();
+        assert!(line.starts_with("{\"error\":\"Invalid command"));
+
+        // Both tokens of the bucket are spent: a flood is dropped.
+        for _ in 0..20 {
+            writer
+                .write_all(b"{\"Move\": {\"direction\": \"North\"}}\n")
+                .await?;
+        }
+        writer.shutdown().await?;
+        let mut commands = 0;
+        loop {
+            match messages.recv().await {
+                Some(BotMessage::Command { .. }) => commands += 1,
+                Some(BotMessage::Leave { id: left }) => {
+                    assert_eq!(left, id);
+                    break;
+                }
+                message => panic!("Unexpected message {message:?}"),
+            }
+        }
+        assert!(commands < 3);
+
+        Ok(())
+    }
+}
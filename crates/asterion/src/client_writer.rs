@@ -0,0 +1,199 @@
+//! Frames are written to each SSH client by a task of its own, so that a slow
+//! client only holds back its own frames and never the game loop.
+
+use crate::metrics::Metrics;
+use crate::AppResult;
+use anyhow::anyhow;
+use asterion_core::Game;
+use frittura_ssh_core::SshWriterProxy;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::task::JoinHandle;
+
+/// Slowest frame rate of a client which cannot keep up.
+const MAX_FRAME_INTERVAL: Duration = Duration::from_millis(800);
+
+/// Interval between the frames of a client, doubled whenever the client is
+/// still receiving the previous frame and brought back one draw step at a
+/// time once it catches up.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameRate {
+    interval: Duration,
+    last_frame: Option<Instant>,
+}
+
+impl Default for FrameRate {
+    fn default() -> Self {
+        Self {
+            interval: Game::draw_time_step(),
+            last_frame: None,
+        }
+    }
+}
+
+impl FrameRate {
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    pub fn is_due(&self, now: Instant) -> bool {
+        self.last_frame
+            .is_none_or(|last_frame| now.duration_since(last_frame) >= self.interval)
+    }
+
+    pub fn slow_down(&mut self) {
+        self.interval = (self.interval * 2).min(MAX_FRAME_INTERVAL);
+    }
+
+    /// Records a frame pushed at `now`.
+    pub fn record_frame(&mut self, now: Instant) {
+        self.interval = self
+            .interval
+            .saturating_sub(Game::draw_time_step())
+            .max(Game::draw_time_step());
+        self.last_frame = Some(now);
+    }
+}
+
+/// Frames are stamped when handed to the writer task, so that the time they
+/// wait for the previous one to be sent is measured too.
+type Frame = (Instant, Vec<u8>);
+
+async fn write_frames(
+    mut writer: SshWriterProxy,
+    mut frames: mpsc::Receiver<Frame>,
+    metrics: Arc<Mutex<Metrics>>,
+) {
+    while let Some((pushed_at, frame)) = frames.recv().await {
+        if writer
+            .write_all(&frame)
+            .and_then(|_| writer.flush())
+            .is_err()
+        {
+            break;
+        }
+        if let Err(err) = writer.send().await {
+            log::warn!("Unable to send frame: {err}");
+            break;
+        }
+        if let Ok(mut metrics) = metrics.lock() {
+            metrics.observe_send(pushed_at.elapsed());
+        }
+    }
+    writer.send_and_close().await;
+}
+
+/// Collects the bytes of the terminal backend and hands them to the writer
+/// task of the client. One frame can wait while the previous one is being
+/// sent, later bytes stay here until there is room again.
+#[derive(Debug)]
+pub struct ClientWriter {
+    sink: Vec<u8>,
+    frames: mpsc::Sender<Frame>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl ClientWriter {
+    pub fn new(writer: SshWriterProxy, metrics: Arc<Mutex<Metrics>>) -> Self {
+        let (frames, receiver) = mpsc::channel(1);
+        Self {
+            sink: vec![],
+            frames,
+            task: Some(tokio::spawn(write_frames(writer, receiver, metrics))),
+        }
+    }
+
+    /// Whether the writer task has picked up every frame pushed so far, so
+    /// that at most one frame is still being sent.
+    pub fn is_idle(&self) -> bool {
+        self.frames.capacity() == self.frames.max_capacity()
+    }
+
+    /// Bytes written since the last push.
+    pub fn pending(&self) -> usize {
+        self.sink.len()
+    }
+
+    /// Drops the bytes written after the first `len` ones.
+    pub fn truncate(&mut self, len: usize) {
+        self.sink.truncate(len);
+    }
+
+    /// Hands the pending bytes to the writer task without waiting for them
+    /// to be sent. Fails once the task is gone.
+    pub fn push(&mut self) -> AppResult<()> {
+        if self.sink.is_empty() {
+            return Ok(());
+        }
+        match self
+            .frames
+            .try_send((Instant::now(), std::mem::take(&mut self.sink)))
+        {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full((_, frame))) => {
+                self.sink = frame;
+                Ok(())
+            }
+            Err(TrySendError::Closed(_)) => Err(anyhow!("Client writer is closed")),
+        }
+    }
+
+    /// Sends the pending bytes, restores the terminal and closes the SSH
+    /// channel, awaited end-to-end. Later pushes fail.
+    pub async fn close(&mut self) {
+        // Swapping in a sender with no receiver ends the writer task.
+        let (closed, _) = mpsc::channel(1);
+        let frames = std::mem::replace(&mut self.frames, closed);
+        if !self.sink.is_empty() {
+            let _ = frames
+                .send((Instant::now(), std::mem::take(&mut self.sink)))
+                .await;
+        }
+        drop(frames);
+        if let Some(task) = self.task.take() {
+            let _ = task.await;
+        }
+    }
+}
+
+impl Write for ClientWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.sink.extend(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FrameRate, MAX_FRAME_INTERVAL};
+    use asterion_core::Game;
+    use std::time::Instant;
+
+    #[test]
+    fn test_frame_rate() {
+        let step = Game::draw_time_step();
+        let now = Instant::now();
+        let mut frame_rate = FrameRate::default();
+        assert!(frame_rate.is_due(now));
+
+        frame_rate.record_frame(now);
+        assert_eq!(frame_rate.interval(), step);
+        assert!(!frame_rate.is_due(now + step / 2));
+        assert!(frame_rate.is_due(now + step));
+
+        for _ in 0..10 {
+            frame_rate.slow_down();
+        }
+        assert_eq!(frame_rate.interval(), MAX_FRAME_INTERVAL);
+        assert!(!frame_rate.is_due(now + step));
+
+        frame_rate.record_frame(now);
+        assert_eq!(frame_rate.interval(), MAX_FRAME_INTERVAL - step);
+    }
+}
@@ -0,0 +1,45 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const DEFAULT_RECONNECT_GRACE: Duration = Duration::from_secs(30);
+const DEFAULT_INPUT_RATE: u32 = 30;
+
+/// Server-side settings, as opposed to the `GameConfig` rules of the game.
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// How long the hero of a disconnected key waits for its player to come
+    /// back. Zero removes it right away.
+    pub reconnect_grace: Duration,
+    /// Where to listen for the operator console, if anywhere.
+    pub admin_socket: Option<PathBuf>,
+    /// Players in the game at once, disconnected heros included. Further
+    /// sessions wait in a queue.
+    pub max_players: Option<usize>,
+    /// Key presses per second accepted from each session, the rest is dropped.
+    pub input_rate: u32,
+    /// Where to serve the Prometheus metrics, if anywhere.
+    pub metrics_addr: Option<SocketAddr>,
+    /// Where to append every game event as a JSON line, if anywhere.
+    pub audit_log: Option<PathBuf>,
+    /// Where to listen for bots speaking the JSON-lines protocol, if anywhere.
+    pub bot_addr: Option<SocketAddr>,
+    /// Heros played by the server while there are fewer players than this,
+    /// leaving one by one as players join.
+    pub npcs: usize,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            reconnect_grace: DEFAULT_RECONNECT_GRACE,
+            admin_socket: None,
+            max_players: None,
+            input_rate: DEFAULT_INPUT_RATE,
+            metrics_addr: None,
+            audit_log: None,
+            bot_addr: None,
+            npcs: 0,
+        }
+    }
+}
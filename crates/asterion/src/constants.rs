@@ -1 +1,3 @@
 pub const UI_SCREEN_SIZE: (u16, u16) = (160, 30);
+/// Below this size there is no room left for the maze, whatever the layout.
+pub const MIN_UI_SCREEN_SIZE: (u16, u16) = (40, 12);
@@ -0,0 +1,86 @@
+//! Deaths, new records and victories, announced to every player in the
+//! sidebar unless they muted it.
+
+use crate::ui::ui::format_duration;
+use asterion_core::{GameEvent, MAX_MAZE_ID};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+const MAX_FEED_ENTRIES: usize = 6;
+const FEED_ENTRY_DURATION: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnnouncementKind {
+    Death,
+    Record,
+    Victory,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Announcements {
+    entries: VecDeque<(Instant, AnnouncementKind, String)>,
+}
+
+impl Announcements {
+    fn push(&mut self, kind: AnnouncementKind, text: String) {
+        self.entries.push_back((Instant::now(), kind, text));
+    }
+
+    pub fn push_events(&mut self, events: &[GameEvent]) {
+        for event in events {
+            match event {
+                GameEvent::HeroDied {
+                    name,
+                    maze_id,
+                    minotaur_name,
+                    ..
+                } => self.push(
+                    AnnouncementKind::Death,
+                    format!(
+                        "{name} was caught by {minotaur_name} in room {}",
+                        maze_id + 1
+                    ),
+                ),
+                GameEvent::Victory {
+                    hero_id,
+                    name,
+                    duration,
+                } => {
+                    // A victory which is also a record is announced only once.
+                    let is_record = events.iter().any(|event| {
+                        matches!(event, GameEvent::NewRecord { hero_id: id, maze_id, .. }
+                            if id == hero_id && *maze_id == MAX_MAZE_ID)
+                    });
+                    let text = format!(
+                        "{name} exited the labyrinth in {}",
+                        format_duration(duration)
+                    );
+                    if is_record {
+                        self.push(AnnouncementKind::Record, format!("{text}, a new record!"));
+                    } else {
+                        self.push(AnnouncementKind::Victory, text);
+                    }
+                }
+                GameEvent::NewRecord { name, maze_id, .. } if *maze_id < MAX_MAZE_ID => self.push(
+                    AnnouncementKind::Record,
+                    format!("{name} set a new record reaching room {}", maze_id + 1),
+                ),
+                _ => {}
+            }
+        }
+
+        while self.entries.len() > MAX_FEED_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries
+            .retain(|(instant, _, _)| instant.elapsed() < FEED_ENTRY_DURATION);
+    }
+
+    /// The most recent entry comes first.
+    pub fn entries(&self) -> impl Iterator<Item = (AnnouncementKind, &str)> {
+        self.entries
+            .iter()
+            .rev()
+            .map(|(_, kind, text)| (*kind, text.as_str()))
+    }
+}
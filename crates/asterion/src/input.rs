@@ -1,5 +1,51 @@
 use asterion_core::{Direction, GameCommand};
 use ratatui::crossterm::event::KeyCode;
+use std::time::Instant;
+
+/// Token bucket letting through at most `rate` inputs per second, with bursts
+/// of up to a second worth of them.
+#[derive(Debug)]
+pub struct InputRateLimiter {
+    rate: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl InputRateLimiter {
+    pub fn new(rate: u32) -> Self {
+        Self {
+            rate: rate as f64,
+            tokens: rate as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Whether an input received at `now` is let through.
+    pub fn allow(&mut self, now: Instant) -> bool {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Handled by the terminal of each session rather than by the game, as the
+/// color mode depends on the terminal the player connects with.
+pub const CYCLE_COLOR_MODE_KEY: KeyCode = KeyCode::Char('c');
+/// Same for the glyphs drawing the pixels of the room, as not every font
+/// has them all.
+pub const CYCLE_RENDERER_KEY: KeyCode = KeyCode::Char('r');
+/// Same for the sidebar shown over the room, only needed by terminals too
+/// narrow to fit it beside it.
+pub const TOGGLE_SIDEBAR_KEY: KeyCode = KeyCode::Tab;
 
 pub fn key_to_command(key_code: KeyCode) -> Option<GameCommand> {
     match key_code {
@@ -7,6 +53,8 @@ pub fn key_to_command(key_code: KeyCode) -> Option<GameCommand> {
             'a' => Some(GameCommand::TurnCounterClockwise),
             'd' => Some(GameCommand::TurnClockwise),
             'w' => Some(GameCommand::CycleUiOptions),
+            'g' => Some(GameCommand::CycleGhostTarget),
+            'm' => Some(GameCommand::ToggleAnnouncements),
             'h' => Some(GameCommand::Move {
                 direction: Direction::West,
             }),
@@ -36,3 +84,29 @@ pub fn key_to_command(key_code: KeyCode) -> Option<GameCommand> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::InputRateLimiter;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_input_rate_limiter() {
+        let mut limiter = InputRateLimiter::new(10);
+        let now = Instant::now();
+
+        // A full bucket lets through a burst of a second worth of inputs.
+        assert_eq!((0..20).filter(|_| limiter.allow(now)).count(), 10);
+        assert!(!limiter.allow(now));
+
+        // Tokens come back at the rate of the limiter.
+        let now = now + Duration::from_millis(250);
+        assert!(limiter.allow(now));
+        assert!(limiter.allow(now));
+        assert!(!limiter.allow(now));
+
+        // And never beyond a second worth of them.
+        let now = now + Duration::from_secs(60);
+        assert_eq!((0..20).filter(|_| limiter.allow(now)).count(), 10);
+    }
+}
@@ -1,10 +1,22 @@
+mod admin;
+mod audit;
+mod bots;
+mod client_writer;
+mod config;
 mod constants;
+mod feed;
 mod input;
+pub mod local;
+mod metrics;
+pub mod players;
 mod server_loop;
+mod session;
 pub mod ssh_game;
+pub mod themes;
 mod tui;
 pub mod ui;
 mod utils;
 
 pub use asterion_core::PlayerId;
+pub use config::ServerConfig;
 pub use utils::{store_path, AppResult};
@@ -0,0 +1,144 @@
+//! Single-player mode playing the game right in the local terminal, without
+//! the SSH server.
+
+use crate::feed::Announcements;
+use crate::input::{key_to_command, CYCLE_COLOR_MODE_KEY, CYCLE_RENDERER_KEY, TOGGLE_SIDEBAR_KEY};
+use crate::ui::color_mode::ColorMode;
+use crate::ui::renderer::Renderer;
+use crate::{ui, AppResult, PlayerId};
+use asterion_core::{Camera, Game, GameConfig, NpcStrategy, SimulatedClock};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::widgets::{Block, Clear, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::time::Instant;
+
+fn render_paused(frame: &mut Frame) {
+    let area = frame.area();
+    let width: u16 = 40;
+    let height: u16 = 3;
+    let popup = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width: width.min(area.width),
+        height: height.min(area.height),
+    };
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new("Paused, 'p' to resume, Esc to quit")
+            .centered()
+            .style(Style::new().yellow().bold())
+            .block(Block::bordered()),
+        popup,
+    );
+}
+
+fn play(
+    terminal: &mut DefaultTerminal,
+    mut game: Game,
+    player_id: PlayerId,
+    clock: SimulatedClock,
+) -> AppResult<()> {
+    let start_instant = Instant::now();
+    let mut announcements = Announcements::default();
+    let mut camera = Camera::default();
+    let mut renderer = Renderer::default();
+    let mut sidebar_overlay = false;
+    let mut color_mode = ColorMode::detect(
+        &std::env::var("TERM").unwrap_or_default(),
+        Some(std::env::var("COLORTERM").unwrap_or_default().as_str()),
+    );
+    let mut paused = false;
+    // The game runs on its own clock, which stands still while paused.
+    let mut game_now = Instant::now();
+    let mut last_tick = Instant::now();
+    let mut next_update = Instant::now();
+    let mut next_draw = Instant::now();
+
+    loop {
+        let now = Instant::now();
+        if !paused {
+            game_now += now - last_tick;
+            clock.set(game_now);
+        }
+        last_tick = now;
+
+        if now >= next_update {
+            next_update = now + Game::update_time_step();
+            if !paused {
+                game.update();
+                announcements.push_events(&game.drain_events());
+            }
+        }
+
+        if now >= next_draw {
+            next_draw = now + Game::draw_time_step();
+            terminal.draw(|frame| {
+                ui::ui::render(
+                    frame,
+                    &game,
+                    player_id,
+                    start_instant,
+                    &announcements,
+                    &mut camera,
+                    renderer,
+                    sidebar_overlay,
+                )
+                .expect("Error while rendering game.");
+                if paused {
+                    render_paused(frame);
+                }
+                color_mode.apply_for_player(frame.buffer_mut(), &game, player_id);
+            })?;
+        }
+
+        let timeout = next_update
+            .min(next_draw)
+            .saturating_duration_since(Instant::now());
+        if !event::poll(timeout)? {
+            continue;
+        }
+        let key_event = match event::read()? {
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => key_event,
+            _ => continue,
+        };
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => return Ok(()),
+            KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Ok(())
+            }
+            KeyCode::Char('p') => paused = !paused,
+            CYCLE_COLOR_MODE_KEY => color_mode = color_mode.next(),
+            CYCLE_RENDERER_KEY => renderer = renderer.next(),
+            TOGGLE_SIDEBAR_KEY => sidebar_overlay = !sidebar_overlay,
+            code if !paused => {
+                if let Some(command) = key_to_command(code) {
+                    game.handle_command(&command, player_id);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn run(config: GameConfig, npcs: usize) -> AppResult<()> {
+    let clock = SimulatedClock::install(Instant::now());
+    let mut game = Game::with_config(config)?;
+    let player_id = PlayerId::new_v4();
+    let name = std::env::var("USER").unwrap_or_else(|_| "hero".to_string());
+    game.add_player(player_id, &name);
+    for index in 0..npcs {
+        let strategy = if index.is_multiple_of(2) {
+            NpcStrategy::Frontier
+        } else {
+            NpcStrategy::WallFollower
+        };
+        game.add_npc(strategy);
+    }
+
+    let mut terminal = ratatui::try_init()?;
+    let result = play(&mut terminal, game, player_id, clock);
+    ratatui::try_restore()?;
+    result
+}
@@ -1,5 +1,9 @@
-use asterion::{ssh_game::AsterionGame, store_path, AppResult};
-use clap::{ArgAction, Parser};
+use asterion::{
+    local, players::PlayerRegistry, ssh_game::AsterionGame, store_path, themes, AppResult,
+    ServerConfig,
+};
+use asterion_core::{daily_seed, DeathPolicy, GameConfig};
+use clap::{ArgAction, Parser, ValueEnum};
 use log::LevelFilter;
 use log4rs::{
     append::file::FileAppender,
@@ -8,15 +12,54 @@ use log4rs::{
     Config,
 };
 use std::fs;
+use std::net::{Ipv4Addr, SocketAddr};
 use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
 
 const DEFAULT_PORT: u16 = 2020;
+const DEFAULT_LIVES: usize = 3;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum DeathPolicyArg {
+    Restart,
+    Lives,
+    Checkpoints,
+    LosePowerUp,
+}
 
 #[derive(Parser, Debug)]
 #[clap(name="Asterion", about = "Find your way in da maze", author, version, long_about = None)]
 struct Args {
     #[clap(long, short = 'p', action=ArgAction::Set, help = "Set port to listen on")]
     port: Option<u16>,
+    #[clap(long, action=ArgAction::SetTrue, help = "Play the daily time trial with ghost runs")]
+    time_trial: bool,
+    #[clap(long, value_enum, default_value_t = DeathPolicyArg::Restart, help = "What happens when a hero is caught")]
+    death_policy: DeathPolicyArg,
+    #[clap(long, action=ArgAction::Set, help = "Number of lives with the 'lives' death policy")]
+    lives: Option<usize>,
+    #[clap(long, action=ArgAction::Set, requires = "room_height", value_parser = clap::value_parser!(u16).range(4..=256), help = "Width in cells of every room, random by default")]
+    room_width: Option<u16>,
+    #[clap(long, action=ArgAction::Set, requires = "room_width", value_parser = clap::value_parser!(u16).range(4..=256), help = "Height in cells of every room, random by default")]
+    room_height: Option<u16>,
+    #[clap(long, action=ArgAction::Set, help = "Seconds a disconnected hero waits for its player to reconnect")]
+    reconnect_grace: Option<u64>,
+    #[clap(long, action=ArgAction::SetTrue, help = "Listen for operator commands on a local Unix socket")]
+    admin: bool,
+    #[clap(long, action=ArgAction::Set, help = "Maximum number of players, the others wait in a queue")]
+    max_players: Option<usize>,
+    #[clap(long, action=ArgAction::Set, help = "Key presses per second accepted from each player")]
+    input_rate: Option<u32>,
+    #[clap(long, action=ArgAction::Set, help = "Serve Prometheus metrics on this local port")]
+    metrics_port: Option<u16>,
+    #[clap(long, action=ArgAction::SetTrue, help = "Log every game event as a JSON line")]
+    audit_log: bool,
+    #[clap(long, action=ArgAction::Set, help = "Accept bots speaking JSON lines on this local port")]
+    bot_port: Option<u16>,
+    #[clap(long, action=ArgAction::Set, help = "Fill the labyrinth with server-played heros up to this number of players")]
+    npcs: Option<usize>,
+    #[clap(long, action=ArgAction::SetTrue, help = "Play alone in this terminal instead of running the server")]
+    local: bool,
 }
 
 #[tokio::main]
@@ -38,8 +81,54 @@ async fn main() -> AppResult<()> {
     images_dir.push("images");
     fs::create_dir_all(&images_dir)?;
 
-    let port = Args::parse().port.unwrap_or(DEFAULT_PORT);
-    let game = AsterionGame::new();
+    let args = Args::parse();
+    let port = args.port.unwrap_or(DEFAULT_PORT);
+    let mut config = if args.time_trial {
+        GameConfig::time_trial(daily_seed(SystemTime::now()))
+    } else {
+        GameConfig::default()
+    };
+    config.death_policy = match args.death_policy {
+        DeathPolicyArg::Restart => DeathPolicy::Restart,
+        DeathPolicyArg::Lives => DeathPolicy::Lives {
+            lives: args.lives.unwrap_or(DEFAULT_LIVES),
+        },
+        DeathPolicyArg::Checkpoints => DeathPolicy::Checkpoints,
+        DeathPolicyArg::LosePowerUp => DeathPolicy::LosePowerUp,
+    };
+    if let (Some(width), Some(height)) = (args.room_width, args.room_height) {
+        config.room_size = Some((width as usize, height as usize));
+    }
+    config.themes = themes::load(&store_path("themes.json")?)?;
+    if args.local {
+        return local::run(config, args.npcs.unwrap_or_default());
+    }
+
+    let mut server_config = ServerConfig::default();
+    if let Some(secs) = args.reconnect_grace {
+        server_config.reconnect_grace = Duration::from_secs(secs);
+    }
+    server_config.max_players = args.max_players;
+    if let Some(input_rate) = args.input_rate {
+        server_config.input_rate = input_rate;
+    }
+    if let Some(metrics_port) = args.metrics_port {
+        server_config.metrics_addr = Some(SocketAddr::from((Ipv4Addr::LOCALHOST, metrics_port)));
+    }
+    if let Some(npcs) = args.npcs {
+        server_config.npcs = npcs;
+    }
+    if let Some(bot_port) = args.bot_port {
+        server_config.bot_addr = Some(SocketAddr::from((Ipv4Addr::LOCALHOST, bot_port)));
+    }
+    if args.audit_log {
+        server_config.audit_log = Some(store_path("audit.jsonl")?);
+    }
+    if args.admin {
+        server_config.admin_socket = Some(store_path("admin/admin.sock")?);
+    }
+    let players = PlayerRegistry::load(store_path("players.json")?)?;
+    let game = AsterionGame::new(config, server_config, players)?;
     frittura_ssh_core::run_server(game, port).await?;
     Ok(())
 }
@@ -0,0 +1,345 @@
+//! Server health metrics, served in the Prometheus text format on a local
+//! HTTP endpoint (`GET /metrics`).
+
+use crate::tui::SessionId;
+use crate::AppResult;
+use asterion_core::{Game, MAX_MAZE_ID};
+use itertools::Itertools;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Sessions whose draw timings are exposed, the slowest ones on average.
+const MAX_LABELLED_SESSIONS: usize = 10;
+/// How long a scraper has to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Sum and count of observed durations, exposed as a Prometheus summary.
+#[derive(Debug, Default, Clone, Copy)]
+struct Timing {
+    sum: Duration,
+    count: u64,
+}
+
+impl Timing {
+    fn observe(&mut self, duration: Duration) {
+        self.sum += duration;
+        self.count += 1;
+    }
+
+    fn mean(&self) -> Duration {
+        self.sum / self.count.max(1) as u32
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Metrics {
+    connected_players: usize,
+    disconnected_players: usize,
+    queued_players: usize,
+    players_per_room: [usize; MAX_MAZE_ID],
+    visibility_cache_sizes: [usize; MAX_MAZE_ID],
+    deaths: usize,
+    victories: usize,
+    dropped_sessions: usize,
+    update: Timing,
+    draw: Timing,
+    session_draws: HashMap<SessionId, Timing>,
+    send: Timing,
+    max_frame_interval: Duration,
+}
+
+impl Metrics {
+    pub fn observe_game(&mut self, game: &Game, connected_players: usize, queued_players: usize) {
+        self.connected_players = connected_players;
+        self.disconnected_players = game.heros().filter(|hero| hero.is_disconnected()).count();
+        self.queued_players = queued_players;
+        for maze_id in 0..MAX_MAZE_ID {
+            self.players_per_room[maze_id] = game.heros_in_maze(maze_id);
+            self.visibility_cache_sizes[maze_id] = game.get_maze(maze_id).visibility_cache_len();
+        }
+        self.deaths = game.total_deaths();
+        self.victories = game.total_victories();
+    }
+
+    pub fn observe_update(&mut self, duration: Duration) {
+        self.update.observe(duration);
+    }
+
+    pub fn observe_draw(&mut self, session_id: SessionId, duration: Duration) {
+        self.draw.observe(duration);
+        self.session_draws
+            .entry(session_id)
+            .or_default()
+            .observe(duration);
+    }
+
+    /// Time from handing a frame to the writer task of its client until it
+    /// is sent.
+    pub fn observe_send(&mut self, duration: Duration) {
+        self.send.observe(duration);
+    }
+
+    pub fn forget_session(&mut self, session_id: &SessionId) {
+        self.session_draws.remove(session_id);
+    }
+
+    /// The frame interval of the slowest client, see
+    /// [`FrameRate`](crate::client_writer::FrameRate).
+    pub fn observe_frame_interval(&mut self, interval: Duration) {
+        self.max_frame_interval = interval;
+    }
+
+    pub fn record_dropped_sessions(&mut self, count: usize) {
+        self.dropped_sessions += count;
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, samples: Vec<(String, String)>| {
+            let _ = writeln!(out, "# HELP asterion_{name} {help}");
+            let _ = writeln!(out, "# TYPE asterion_{name} {kind}");
+            for (labels, value) in samples {
+                let _ = writeln!(out, "asterion_{name}{labels} {value}");
+            }
+        };
+        let value = |value: usize| vec![(String::new(), value.to_string())];
+        let per_room = |values: &[usize; MAX_MAZE_ID]| {
+            values
+                .iter()
+                .enumerate()
+                .map(|(maze_id, value)| (format!("{{room=\"{maze_id}\"}}"), value.to_string()))
+                .collect()
+        };
+        let timing = |timing: &Timing| {
+            vec![
+                ("_sum".to_string(), timing.sum.as_secs_f64().to_string()),
+                ("_count".to_string(), timing.count.to_string()),
+            ]
+        };
+        let session_draws = self
+            .session_draws
+            .iter()
+            .sorted_by_key(|(session_id, timing)| (std::cmp::Reverse(timing.mean()), **session_id))
+            .take(MAX_LABELLED_SESSIONS)
+            .flat_map(|(session_id, timing)| {
+                let labels = format!("{{session=\"{session_id}\"}}");
+                [
+                    (
+                        format!("_sum{labels}"),
+                        timing.sum.as_secs_f64().to_string(),
+                    ),
+                    (format!("_count{labels}"), timing.count.to_string()),
+                ]
+            })
+            .collect();
+
+        metric(
+            "connected_players",
+            "gauge",
+            "Players with an open session.",
+            value(self.connected_players),
+        );
+        metric(
+            "disconnected_players",
+            "gauge",
+            "Heros waiting for their player to reconnect.",
+            value(self.disconnected_players),
+        );
+        metric(
+            "queued_players",
+            "gauge",
+            "Sessions waiting for a free spot.",
+            value(self.queued_players),
+        );
+        metric(
+            "room_players",
+            "gauge",
+            "Heros in each room.",
+            per_room(&self.players_per_room),
+        );
+        metric(
+            "visibility_cache_entries",
+            "gauge",
+            "Cached visible positions of each room.",
+            per_room(&self.visibility_cache_sizes),
+        );
+        metric(
+            "deaths_total",
+            "counter",
+            "Heros caught by a minotaur.",
+            value(self.deaths),
+        );
+        metric(
+            "victories_total",
+            "counter",
+            "Heros who made it out of the labyrinth.",
+            value(self.victories),
+        );
+        metric(
+            "dropped_sessions_total",
+            "counter",
+            "Sessions dropped after a draw or push error.",
+            value(self.dropped_sessions),
+        );
+        metric(
+            "update_seconds",
+            "summary",
+            "Duration of Game::update.",
+            timing(&self.update),
+        );
+        metric(
+            "draw_seconds",
+            "summary",
+            "Duration of drawing the frame of a player.",
+            timing(&self.draw),
+        );
+        metric(
+            "session_draw_seconds",
+            "summary",
+            "Duration of drawing the frames of the slowest sessions.",
+            session_draws,
+        );
+        metric(
+            "send_seconds",
+            "summary",
+            "Time from handing a frame to the writer task until it is sent.",
+            timing(&self.send),
+        );
+        metric(
+            "frame_interval_seconds_max",
+            "gauge",
+            "Interval between the frames of the slowest client.",
+            vec![(
+                String::new(),
+                self.max_frame_interval.as_secs_f64().to_string(),
+            )],
+        );
+
+        out
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, metrics: Arc<Mutex<Metrics>>) -> AppResult<()> {
+    let mut buffer = [0; 1024];
+    let read = tokio::time::timeout(READ_TIMEOUT, stream.read(&mut buffer)).await??;
+    let request = String::from_utf8_lossy(&buffer[..read]);
+
+    let response = if request.starts_with("GET /metrics ") {
+        let body = metrics
+            .lock()
+            .map(|metrics| metrics.render())
+            .unwrap_or_default();
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    } else {
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+    };
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+
+    Ok(())
+}
+
+pub fn spawn(addr: SocketAddr, metrics: Arc<Mutex<Metrics>>) -> AppResult<SocketAddr> {
+    let listener = std::net::TcpListener::bind(addr)?;
+    listener.set_nonblocking(true)?;
+    let listener = TcpListener::from_std(listener)?;
+    let local_addr = listener.local_addr()?;
+    log::info!("Serving metrics on http://{local_addr}/metrics");
+
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let metrics = metrics.clone();
+                    tokio::spawn(async move {
+                        if let Err(err) = handle_connection(stream, metrics).await {
+                            log::warn!("Metrics connection error: {err}");
+                        }
+                    });
+                }
+                Err(err) => log::error!("Metrics endpoint error: {err}"),
+            }
+        }
+    });
+
+    Ok(local_addr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{spawn, Metrics, MAX_LABELLED_SESSIONS};
+    use crate::AppResult;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    use uuid::Uuid;
+
+    async fn scrape(addr: std::net::SocketAddr, path: &str) -> AppResult<String> {
+        let mut stream = TcpStream::connect(addr).await?;
+        stream
+            .write_all(format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").as_bytes())
+            .await?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await?;
+        Ok(response)
+    }
+
+    #[tokio::test]
+    async fn test_scrape_metrics() -> AppResult<()> {
+        let metrics = Arc::new(Mutex::new(Metrics::default()));
+        {
+            let mut metrics = metrics.lock().unwrap();
+            metrics.observe_update(Duration::from_millis(2));
+            metrics.observe_update(Duration::from_millis(4));
+            metrics.record_dropped_sessions(1);
+            metrics.observe_frame_interval(Duration::from_millis(100));
+            metrics.observe_send(Duration::from_millis(30));
+        }
+        let slowest = Uuid::new_v4();
+        let fastest = Uuid::new_v4();
+        {
+            let mut metrics = metrics.lock().unwrap();
+            metrics.observe_draw(slowest, Duration::from_millis(50));
+            metrics.observe_draw(fastest, Duration::from_millis(1));
+            for _ in 1..MAX_LABELLED_SESSIONS {
+                metrics.observe_draw(Uuid::new_v4(), Duration::from_millis(10));
+            }
+        }
+        let addr = spawn("127.0.0.1:0".parse()?, metrics.clone())?;
+
+        let response = scrape(addr, "/metrics").await?;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("# TYPE asterion_update_seconds summary"));
+        assert!(response.contains("asterion_update_seconds_count 2"));
+        assert!(response.contains("asterion_update_seconds_sum 0.006"));
+        assert!(response.contains("asterion_dropped_sessions_total 1"));
+        assert!(response.contains("asterion_frame_interval_seconds_max 0.1"));
+        assert!(response.contains("asterion_room_players{room=\"0\"} 0"));
+        assert!(response.contains("asterion_send_seconds_count 1"));
+        assert!(response.contains("asterion_draw_seconds_count 11"));
+        // Only the slowest sessions are labelled.
+        assert!(response.contains(&format!(
+            "asterion_session_draw_seconds_sum{{session=\"{slowest}\"}} 0.05"
+        )));
+        assert!(!response.contains(&fastest.to_string()));
+
+        metrics.lock().unwrap().forget_session(&slowest);
+        let response = scrape(addr, "/metrics").await?;
+        assert!(!response.contains(&slowest.to_string()));
+        assert!(response.contains(&fastest.to_string()));
+
+        let response = scrape(addr, "/").await?;
+        assert!(response.starts_with("HTTP/1.1 404 Not Found"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,294 @@
+//! Persistent identities for players authenticating with a public key. Their
+//! `PlayerId` is derived from the key fingerprint, so that the same key always
+//! gets the same reserved name, preferences and stats. Everyone else keeps
+//! getting an ephemeral identity per session.
+
+use crate::utils::sanitize_username;
+use crate::{AppResult, PlayerId};
+use asterion_core::{to_player_name, Entity, GhostTarget, Hero, UiOptions};
+use frittura_ssh_core::{HashAlg, PublicKey};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use tokio::sync::watch;
+use uuid::Builder;
+
+/// Profiles of keys which never joined the game are dropped beyond this
+/// count, oldest first, freeing their names.
+const MAX_UNCLAIMED_PROFILES: usize = 256;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerProfile {
+    pub fingerprint: String,
+    pub name: String,
+    pub ui_options: UiOptions,
+    pub ghost_target: GhostTarget,
+    pub sessions: usize,
+    pub victories: usize,
+    pub deaths: usize,
+    pub best_room: usize,
+    pub best_time: Option<Duration>,
+    #[serde(default)]
+    pub banned: bool,
+    #[serde(default)]
+    pub announcements_muted: bool,
+    /// Seconds since the Unix epoch when the key was first seen.
+    #[serde(default)]
+    pub registered_at: u64,
+}
+
+type Profiles = HashMap<PlayerId, PlayerProfile>;
+
+/// Serializes and writes the profiles to `path` off the async runtime.
+/// Versions superseded before they could be written are skipped.
+fn spawn_writer(path: PathBuf) -> watch::Sender<Option<Profiles>> {
+    let (sender, mut receiver) = watch::channel(None::<Profiles>);
+    tokio::spawn(async move {
+        while receiver.changed().await.is_ok() {
+            let Some(profiles) = receiver.borrow_and_update().clone() else {
+                continue;
+            };
+            let path = path.clone();
+            let saved = tokio::task::spawn_blocking(move || -> AppResult<()> {
+                std::fs::write(path, serde_json::to_string_pretty(&profiles)?)?;
+                Ok(())
+            })
+            .await;
+            match saved {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => log::error!("Unable to save players: {err}"),
+                Err(err) => log::error!("Unable to save players: {err}"),
+            }
+        }
+    });
+    sender
+}
+
+#[derive(Debug, Default)]
+pub struct PlayerRegistry {
+    writer: Option<watch::Sender<Option<Profiles>>>,
+    profiles: Profiles,
+}
+
+impl PlayerRegistry {
+    /// Hands a copy of the profiles to the writer task, without waiting for
+    /// the disk.
+    fn save(&self) -> AppResult<()> {
+        if let Some(writer) = self.writer.as_ref() {
+            writer.send_replace(Some(self.profiles.clone()));
+        }
+        Ok(())
+    }
+
+    fn is_reserved(&self, name: &str) -> bool {
+        self.profiles
+            .values()
+            .any(|profile| profile.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Reads the profiles saved at `path`, where later changes are saved by
+    /// a task of their own, so this must be called within a tokio runtime.
+    pub fn load(path: PathBuf) -> AppResult<Self> {
+        let profiles = if path.exists() {
+            serde_json::from_str(&std::fs::read_to_string(&path)?)?
+        } else {
+            HashMap::new()
+        };
+
+        Ok(Self {
+            writer: Some(spawn_writer(path)),
+            profiles,
+        })
+    }
+
+    pub fn player_id(key: &PublicKey) -> PlayerId {
+        let fingerprint = key.fingerprint(HashAlg::Sha256);
+        let mut bytes = [0; 16];
+        bytes.copy_from_slice(&fingerprint.as_bytes()[..16]);
+        Builder::from_custom_bytes(bytes).into_uuid()
+    }
+
+    /// Returns the persistent id of the key, registering a profile with a
+    /// reserved name the first time the key is seen.
+    pub fn identify(&mut self, username: &str, key: &PublicKey) -> AppResult<PlayerId> {
+        let id = Self::player_id(key);
+        if !self.profiles.contains_key(&id) {
+            let fingerprint = key.fingerprint(HashAlg::Sha256).to_string();
+            self.register(id, fingerprint, username)?;
+        }
+
+        Ok(id)
+    }
+
+    fn register(&mut self, id: PlayerId, fingerprint: String, username: &str) -> AppResult<()> {
+        // Names with a `#` are left to anonymous players, see `to_player_name`.
+        let username = sanitize_username(username.split('#').next().unwrap_or_default());
+        let mut name = username.clone();
+        let rng = &mut rand::rng();
+        while self.is_reserved(&name) {
+            name = to_player_name(rng, &username);
+        }
+
+        log::info!("Reserved name {name} for a new key");
+        let registered_at = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|since_epoch| since_epoch.as_secs())
+            .unwrap_or_default();
+        self.profiles.insert(
+            id,
+            PlayerProfile {
+                fingerprint,
+                name,
+                ui_options: UiOptions::Dark,
+                ghost_target: GhostTarget::Leader,
+                sessions: 0,
+                victories: 0,
+                deaths: 0,
+                best_room: 0,
+                best_time: None,
+                banned: false,
+                announcements_muted: false,
+                registered_at,
+            },
+        );
+        self.prune_unclaimed(id);
+        self.save()
+    }
+
+    /// Drops the oldest profiles of keys which never joined the game, banned
+    /// ones and `keep` aside, beyond `MAX_UNCLAIMED_PROFILES`.
+    fn prune_unclaimed(&mut self, keep: PlayerId) {
+        let mut unclaimed = self
+            .profiles
+            .iter()
+            .filter(|(&id, profile)| id != keep && profile.sessions == 0 && !profile.banned)
+            .map(|(&id, profile)| (profile.registered_at, id))
+            .collect::<Vec<_>>();
+        // The kept profile is unclaimed too.
+        let excess = (unclaimed.len() + 1).saturating_sub(MAX_UNCLAIMED_PROFILES);
+        unclaimed.sort();
+        for (_, id) in unclaimed.into_iter().take(excess) {
+            self.profiles.remove(&id);
+        }
+    }
+
+    pub fn profile(&self, id: &PlayerId) -> Option<&PlayerProfile> {
+        self.profiles.get(id)
+    }
+
+    /// Looks a profile up by its name or key fingerprint.
+    pub fn find(&self, name_or_fingerprint: &str) -> Option<PlayerId> {
+        self.profiles
+            .iter()
+            .find(|(_, profile)| {
+                profile.fingerprint == name_or_fingerprint
+                    || profile.name.eq_ignore_ascii_case(name_or_fingerprint)
+            })
+            .map(|(&id, _)| id)
+    }
+
+    pub fn is_banned(&self, id: &PlayerId) -> bool {
+        self.profiles
+            .get(id)
+            .map(|profile| profile.banned)
+            .unwrap_or_default()
+    }
+
+    /// Bans or unbans a key. Returns false if the key is unknown.
+    pub fn set_banned(&mut self, id: &PlayerId, banned: bool) -> AppResult<bool> {
+        if let Some(profile) = self.profiles.get_mut(id) {
+            profile.banned = banned;
+            self.save()?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Counts a session of the key as its hero joins the game, so that the
+    /// profile is not dropped while it is in use.
+    pub fn record_join(&mut self, id: &PlayerId) -> AppResult<()> {
+        if let Some(profile) = self.profiles.get_mut(id) {
+            profile.sessions += 1;
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Stores the preferences and stats of a hero leaving the game.
+    pub fn record_session(
+        &mut self,
+        hero: &Hero,
+        record: Option<(usize, Duration)>,
+    ) -> AppResult<()> {
+        let profile = if let Some(profile) = self.profiles.get_mut(&hero.id()) {
+            profile
+        } else {
+            return Ok(());
+        };
+
+        profile.ui_options = hero.ui_options();
+        profile.ghost_target = hero.ghost_target();
+        profile.announcements_muted = hero.announcements_muted();
+        profile.victories += hero.victories();
+        profile.deaths += hero.deaths_by_room().iter().sum::<usize>();
+
+        if let Some((room, duration)) = record {
+            let is_better = room > profile.best_room
+                || (room == profile.best_room
+                    && profile
+                        .best_time
+                        .map(|best| duration < best)
+                        .unwrap_or(true));
+            if is_better {
+                profile.best_room = room;
+                profile.best_time = Some(duration);
+            }
+        }
+
+        self.save()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PlayerRegistry, MAX_UNCLAIMED_PROFILES};
+    use crate::{AppResult, PlayerId};
+
+    #[test]
+    fn test_unclaimed_profiles_are_capped() -> AppResult<()> {
+        let mut registry = PlayerRegistry::default();
+        let claimed = PlayerId::new_v4();
+        registry.register(claimed, "claimed".to_string(), "claimed")?;
+        registry.record_join(&claimed)?;
+
+        let mut ids = vec![];
+        for idx in 0..MAX_UNCLAIMED_PROFILES + 10 {
+            let id = PlayerId::new_v4();
+            registry.register(id, idx.to_string(), "player")?;
+            registry.profiles.get_mut(&id).unwrap().registered_at = idx as u64;
+            ids.push(id);
+        }
+
+        assert_eq!(registry.profiles.len(), MAX_UNCLAIMED_PROFILES + 1);
+        assert!(registry.profile(&claimed).is_some());
+        assert!(ids[..10].iter().all(|id| registry.profile(id).is_none()));
+        assert!(ids[10..].iter().all(|id| registry.profile(id).is_some()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reserved_names_leave_out_anonymous_ones() -> AppResult<()> {
+        let mut registry = PlayerRegistry::default();
+        let id = PlayerId::new_v4();
+        registry.register(id, "theseus".to_string(), "theseus#123")?;
+        assert_eq!(registry.profile(&id).unwrap().name, "theseus");
+
+        let id = PlayerId::new_v4();
+        registry.register(id, "anonymous".to_string(), "#123")?;
+        assert_eq!(registry.profile(&id).unwrap().name, "hero");
+
+        Ok(())
+    }
+}
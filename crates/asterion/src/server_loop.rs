@@ -1,84 +1,678 @@
-//! Centralized game-task that owns the `Game` (mazes, heroes, minotaurs)
-//! and routes per-player input to it.
+//! Centralized game-task that owns the `Game` (mazes, heroes, minotaurs),
+//! routes per-player input to it and publishes a snapshot of it every frame
+//! for the sessions to draw.
 
-use crate::input::key_to_command;
-use crate::tui::Tui;
-use crate::PlayerId;
-use asterion_core::Game;
+use crate::admin::{AdminCommand, AdminRequest};
+use crate::audit::AuditLog;
+use crate::bots::{BotFrame, BotMessage};
+use crate::feed::Announcements;
+use crate::input::{key_to_command, CYCLE_COLOR_MODE_KEY, CYCLE_RENDERER_KEY, TOGGLE_SIDEBAR_KEY};
+use crate::metrics::Metrics;
+use crate::players::PlayerRegistry;
+use crate::session::{Session, Snapshot};
+use crate::tui::{SessionId, Tui};
+use crate::ui::tiles;
+use crate::utils::store_path;
+use crate::{AppResult, PlayerId, ServerConfig};
+use anyhow::anyhow;
+use asterion_core::{daily_seed, Camera, Entity, Game, GameConfig, NpcStrategy};
 use frittura_ssh_core::TerminalEvent;
+use itertools::Itertools;
 use ratatui::crossterm::event::KeyCode;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 use tokio::select;
-use tokio::sync::mpsc::Receiver;
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::sync::watch;
+
+const BROADCAST_DURATION: Duration = Duration::from_secs(10);
+const MAX_QUEUE_LEN: usize = 64;
+/// Pixels per tile in the snapshots saved from the admin console.
+const SNAPSHOT_SCALE: u32 = 4;
+
+/// Where the game task receives players, their input and operator commands.
+pub struct ServerChannels {
+    pub clients: Receiver<Tui>,
+    pub terminal_events: Receiver<(PlayerId, SessionId, TerminalEvent)>,
+    pub admin: Receiver<AdminRequest>,
+    pub bots: Receiver<BotMessage>,
+}
+
+struct ServerState {
+    game: Game,
+    config: ServerConfig,
+    players: Arc<Mutex<PlayerRegistry>>,
+    metrics: Arc<Mutex<Metrics>>,
+    sessions: HashMap<PlayerId, Session>,
+    idle_warnings: HashMap<PlayerId, u32>,
+    disconnections: HashMap<PlayerId, Instant>,
+    broadcast: Option<(String, Instant)>,
+    queue: VecDeque<Session>,
+    bots: HashMap<PlayerId, Sender<BotFrame>>,
+    announcements: Announcements,
+    audit_log: Option<AuditLog>,
+    snapshots: watch::Sender<Option<Arc<Snapshot>>>,
+    dropped_sessions: Sender<(PlayerId, SessionId)>,
+}
+
+impl ServerState {
+    fn has_profile(&self, player_id: &PlayerId) -> bool {
+        self.players
+            .lock()
+            .map(|players| players.profile(player_id).is_some())
+            .unwrap_or_default()
+    }
+
+    /// Players in the game, npcs aside.
+    fn number_of_players(&self) -> usize {
+        self.game.number_of_players() - self.game.npcs().count()
+    }
+
+    fn is_full(&self) -> bool {
+        self.config
+            .max_players
+            .is_some_and(|max_players| self.number_of_players() >= max_players)
+    }
+
+    /// Adds or removes npcs so that there are `npcs` heros in the game.
+    fn balance_npcs(&mut self) {
+        let wanted = self.config.npcs.saturating_sub(self.number_of_players());
+        let npcs = self.game.npcs().count();
+        if npcs < wanted {
+            let strategy = if npcs.is_multiple_of(2) {
+                NpcStrategy::Frontier
+            } else {
+                NpcStrategy::WallFollower
+            };
+            let npc_id = self.game.add_npc(strategy);
+            log::info!("Npc {npc_id} joined the game");
+        } else if npcs > wanted {
+            let npc_id = self.game.npcs().map(|(&id, _)| id).next();
+            if let Some(npc_id) = npc_id {
+                log::info!("Npc {npc_id} left the game");
+                self.game.remove_player(&npc_id);
+            }
+        }
+    }
+
+    /// Starts drawing the frames of a new session, then lets it in the game
+    /// or in the queue.
+    async fn start_session(&mut self, tui: Tui) {
+        let session = Session::spawn(
+            tui,
+            self.snapshots.subscribe(),
+            self.metrics.clone(),
+            self.dropped_sessions.clone(),
+        );
+        self.add_session(session).await;
+    }
+
+    async fn add_session(&mut self, session: Session) {
+        let player_id = session.id;
+        if self.game.get_hero(&player_id).is_none() && self.is_full() {
+            self.enqueue(session).await;
+            return;
+        }
+
+        if self.game.get_hero(&player_id).is_none() {
+            let profile = self
+                .players
+                .lock()
+                .ok()
+                .and_then(|players| players.profile(&player_id).cloned());
+            if let Some(profile) = profile {
+                if let Ok(mut players) = self.players.lock() {
+                    if let Err(err) = players.record_join(&player_id) {
+                        log::warn!("Unable to save player {player_id}: {err}");
+                    }
+                }
+                self.game.add_player_with_name(player_id, &profile.name);
+                self.game.set_hero_preferences(
+                    &player_id,
+                    profile.ui_options,
+                    profile.ghost_target,
+                    profile.announcements_muted,
+                );
+            } else {
+                self.game.add_player(player_id, session.username());
+            }
+        } else if self.disconnections.remove(&player_id).is_some() {
+            log::info!("Player {player_id} reconnected");
+            self.game.reconnect_player(&player_id);
+        }
+
+        // The same key connected again: the new session takes over the hero.
+        if let Some(old_session) = self.sessions.insert(player_id, session) {
+            old_session.close().await;
+        }
+    }
+
+    async fn enqueue(&mut self, session: Session) {
+        let player_id = session.id;
+        if let Some(queued) = self.queue.iter_mut().find(|queued| queued.id == player_id) {
+            std::mem::replace(queued, session).close().await;
+        } else if self.queue.len() >= MAX_QUEUE_LEN {
+            log::info!("Queue is full, dropping session of {player_id}");
+            session.close().await;
+        } else {
+            log::info!("Server is full, {player_id} is waiting in the queue");
+            self.queue.push_back(session);
+        }
+    }
+
+    async fn admit_queued(&mut self) {
+        while !self.is_full() {
+            if let Some(session) = self.queue.pop_front() {
+                self.add_session(session).await;
+            } else {
+                break;
+            }
+        }
+    }
+
+    async fn handle_queued_event(
+        &mut self,
+        player_id: PlayerId,
+        session_id: SessionId,
+        event: TerminalEvent,
+    ) {
+        let index = if let Some(index) = self
+            .queue
+            .iter()
+            .position(|queued| queued.session_id() == session_id)
+        {
+            index
+        } else {
+            return;
+        };
+
+        match event {
+            TerminalEvent::Key(key_event) if key_event.code != KeyCode::Esc => {
+                self.idle_warnings.remove(&player_id);
+            }
+            TerminalEvent::Key(_) | TerminalEvent::Quit => {
+                self.idle_warnings.remove(&player_id);
+                if let Some(session) = self.queue.remove(index) {
+                    session.close().await;
+                }
+            }
+            TerminalEvent::Resize(width, height) => {
+                self.queue[index].resize(width, height);
+            }
+            TerminalEvent::IdleWarning(secs) => {
+                self.idle_warnings.insert(player_id, secs);
+            }
+            _ => {}
+        }
+    }
+
+    /// Keeps the hero of a known key around for the reconnect grace period,
+    /// removes anybody else.
+    async fn disconnect_player(&mut self, player_id: PlayerId) {
+        if self.config.reconnect_grace.is_zero() || !self.has_profile(&player_id) {
+            self.remove_player(player_id).await;
+            return;
+        }
+
+        log::info!("Player {player_id} disconnected");
+        self.game.disconnect_player(&player_id);
+        self.disconnections.insert(player_id, Instant::now());
+        self.idle_warnings.remove(&player_id);
+        if let Some(session) = self.sessions.remove(&player_id) {
+            session.close().await;
+        }
+    }
+
+    /// Lets go of a session which failed to draw or push a frame.
+    async fn drop_session(&mut self, player_id: PlayerId, session_id: SessionId) {
+        if self
+            .sessions
+            .get(&player_id)
+            .map(|session| session.session_id())
+            == Some(session_id)
+        {
+            self.disconnect_player(player_id).await;
+        } else if let Some(index) = self
+            .queue
+            .iter()
+            .position(|queued| queued.session_id() == session_id)
+        {
+            if let Some(session) = self.queue.remove(index) {
+                session.close().await;
+            }
+        } else {
+            return;
+        }
+        if let Ok(mut metrics) = self.metrics.lock() {
+            metrics.record_dropped_sessions(1);
+        }
+    }
+
+    /// Hands the sessions what to draw next.
+    fn publish_snapshot(&mut self) {
+        if self
+            .broadcast
+            .as_ref()
+            .is_some_and(|(_, instant)| instant.elapsed() > BROADCAST_DURATION)
+        {
+            self.broadcast = None;
+        }
+        if self.sessions.is_empty() && self.queue.is_empty() {
+            return;
+        }
+
+        let snapshot = Snapshot {
+            game: self.game.clone(),
+            announcements: self.announcements.clone(),
+            broadcast: self.broadcast.as_ref().map(|(message, _)| message.clone()),
+            idle_warnings: self.idle_warnings.clone(),
+            playing: self
+                .sessions
+                .values()
+                .map(|session| session.session_id())
+                .collect(),
+            queue: self
+                .queue
+                .iter()
+                .map(|session| session.session_id())
+                .collect(),
+        };
+        self.snapshots.send_replace(Some(Arc::new(snapshot)));
+    }
+
+    async fn remove_expired_disconnections(&mut self) {
+        let expired = self
+            .disconnections
+            .iter()
+            .filter(|(_, instant)| instant.elapsed() >= self.config.reconnect_grace)
+            .map(|(&player_id, _)| player_id)
+            .collect::<Vec<_>>();
+        for player_id in expired {
+            log::info!("Player {player_id} did not reconnect in time");
+            self.remove_player(player_id).await;
+        }
+    }
+
+    /// Saves the whole room of a player, as far as they know it, as PNG. The
+    /// image is encoded and written off the game task.
+    fn snapshot(&self, player_id: PlayerId) -> AppResult<PathBuf> {
+        let hero = self
+            .game
+            .get_hero(&player_id)
+            .ok_or(anyhow!("Player {player_id} is not in the game"))?;
+        let (width, height) = self.game.get_maze(hero.maze_id()).dimensions();
+        let mut camera = Camera::new(width, height);
+        camera.follow(hero.maze_id(), hero.position(), (width, height));
+
+        let tiles = self.game.tiles(player_id, &camera)?;
+        let theme = self.game.theme(hero.ui_options()).clone();
+        let path = store_path(&format!("snapshot-{player_id}.png"))?;
+        let image_path = path.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(err) = tiles::to_png_image(&tiles, &theme, SNAPSHOT_SCALE).save(&image_path)
+            {
+                log::error!("Unable to save snapshot {}: {err}", image_path.display());
+            }
+        });
+        Ok(path)
+    }
+
+    /// Finds a player in the game by id or name, or a known key by name or
+    /// fingerprint.
+    fn find_player(&self, player: &str) -> Option<PlayerId> {
+        if let Ok(player_id) = player.parse::<PlayerId>() {
+            return Some(player_id);
+        }
+
+        self.game
+            .heros()
+            .find(|hero| hero.name().eq_ignore_ascii_case(player))
+            .map(|hero| hero.id())
+            .or_else(|| {
+                self.players
+                    .lock()
+                    .ok()
+                    .and_then(|players| players.find(player))
+            })
+    }
+
+    fn list_players(&self) -> String {
+        let lines = self
+            .game
+            .heros()
+            .sorted_by_key(|hero| hero.name())
+            .map(|hero| {
+                let connection = if hero.is_disconnected() {
+                    "disconnected"
+                } else {
+                    "connected"
+                };
+                let identity = if self.has_profile(&hero.id()) {
+                    "key"
+                } else if self.bots.contains_key(&hero.id()) {
+                    "bot"
+                } else if self.game.is_npc(&hero.id()) {
+                    "npc"
+                } else {
+                    "anonymous"
+                };
+                format!(
+                    "{} {} room {} {} {identity} {connection}",
+                    hero.id(),
+                    hero.name(),
+                    hero.maze_id(),
+                    hero.state
+                )
+            })
+            .collect_vec();
+
+        let mut reply = if lines.is_empty() {
+            "No players".to_string()
+        } else {
+            lines.join("\n")
+        };
+        if !self.queue.is_empty() {
+            reply.push_str(&format!("\n{} waiting in the queue", self.queue.len()));
+        }
+        reply
+    }
+
+    async fn handle_admin_command(&mut self, command: AdminCommand) -> String {
+        log::info!("Admin command: {command:?}");
+        match command {
+            AdminCommand::List => self.list_players(),
+
+            AdminCommand::Kick { player } => {
+                let player_id = if let Some(player_id) = self.find_player(&player) {
+                    player_id
+                } else {
+                    return format!("No player {player}");
+                };
+                if self.game.get_hero(&player_id).is_none() {
+                    return format!("Player {player} is not in the game");
+                }
+                self.remove_player(player_id).await;
+                format!("Kicked {player_id}")
+            }
+
+            AdminCommand::Ban { player } => {
+                let player_id = if let Some(player_id) = self.find_player(&player) {
+                    player_id
+                } else {
+                    return format!("No player {player}");
+                };
+                let banned = self
+                    .players
+                    .lock()
+                    .map_err(|_| anyhow!("Player registry lock poisoned"))
+                    .and_then(|mut players| players.set_banned(&player_id, true));
+                match banned {
+                    Ok(true) => {
+                        self.remove_player(player_id).await;
+                        format!("Banned {player_id}")
+                    }
+                    Ok(false) => {
+                        format!("Player {player} has no key to ban, kick them instead")
+                    }
+                    Err(err) => format!("Unable to ban {player}: {err}"),
+                }
+            }
+
+            AdminCommand::Unban { player } => {
+                let player_id = if let Some(player_id) = self.find_player(&player) {
+                    player_id
+                } else {
+                    return format!("No player {player}");
+                };
+                let unbanned = self
+                    .players
+                    .lock()
+                    .map_err(|_| anyhow!("Player registry lock poisoned"))
+                    .and_then(|mut players| players.set_banned(&player_id, false));
+                match unbanned {
+                    Ok(true) => format!("Unbanned {player_id}"),
+                    Ok(false) => format!("No key for {player}"),
+                    Err(err) => format!("Unable to unban {player}: {err}"),
+                }
+            }
+
+            AdminCommand::Broadcast { message } => {
+                self.broadcast = Some((message, Instant::now()));
+                format!("Broadcast to {} players", self.sessions.len())
+            }
+
+            AdminCommand::ResetRoom { maze_id } => match self.game.reset_room(maze_id) {
+                Ok(()) => format!("Room {maze_id} has been rebuilt"),
+                Err(err) => err.to_string(),
+            },
+
+            AdminCommand::SetMinotaurs { maze_id, count } => {
+                match self.game.set_minotaurs_in_maze(maze_id, count) {
+                    Ok(()) => format!("Room {maze_id} has {count} minotaurs"),
+                    Err(err) => err.to_string(),
+                }
+            }
+
+            AdminCommand::Snapshot { player } => {
+                let player_id = if let Some(player_id) = self.find_player(&player) {
+                    player_id
+                } else {
+                    return format!("No player {player}");
+                };
+                match self.snapshot(player_id) {
+                    Ok(path) => format!("Saving {}", path.display()),
+                    Err(err) => err.to_string(),
+                }
+            }
+        }
+    }
+
+    async fn handle_bot_message(&mut self, message: BotMessage) {
+        match message {
+            BotMessage::Join { id, name, frames } => {
+                if self.is_full() {
+                    let _ = frames.try_send(BotFrame::Error("The labyrinth is full".to_string()));
+                    return;
+                }
+                log::info!("Bot {name} joined as {id}");
+                self.game.add_player(id, &name);
+                self.bots.insert(id, frames);
+            }
+            BotMessage::Command { id, command } => {
+                if self.bots.contains_key(&id) {
+                    self.game.handle_command(&command, id);
+                }
+            }
+            BotMessage::Leave { id } => {
+                if self.bots.contains_key(&id) {
+                    self.remove_player(id).await;
+                }
+            }
+        }
+    }
+
+    /// Sends every bot what its hero sees, skipping those lagging behind.
+    fn send_observations(&self) {
+        for (&bot_id, frames) in self.bots.iter() {
+            if frames.capacity() == 0 {
+                continue;
+            }
+            match self.game.observe(bot_id) {
+                Ok(observation) => {
+                    let _ = frames.try_send(BotFrame::Observation(Box::new(observation)));
+                }
+                Err(err) => log::warn!("observe error for bot {bot_id}: {err}"),
+            }
+        }
+    }
+
+    async fn remove_player(&mut self, player_id: PlayerId) {
+        if let (Some(hero), Ok(mut players)) = (self.game.get_hero(&player_id), self.players.lock())
+        {
+            if let Err(err) = players.record_session(hero, self.game.hero_record(&player_id)) {
+                log::warn!("Unable to save player {player_id}: {err}");
+            }
+        }
+        self.game.remove_player(&player_id);
+        self.bots.remove(&player_id);
+        self.idle_warnings.remove(&player_id);
+        self.disconnections.remove(&player_id);
+        if let Some(session) = self.sessions.remove(&player_id) {
+            session.close().await;
+        }
+    }
+}
 
 pub fn spawn(
-    mut client_receiver: Receiver<Tui>,
-    mut terminal_event_receiver: Receiver<(PlayerId, TerminalEvent)>,
+    config: GameConfig,
+    server_config: ServerConfig,
+    players: Arc<Mutex<PlayerRegistry>>,
+    metrics: Arc<Mutex<Metrics>>,
+    mut channels: ServerChannels,
 ) {
     tokio::spawn(async move {
-        let mut game = match Game::new() {
+        let game = match Game::with_config(config) {
             Ok(g) => g,
             Err(err) => {
                 log::error!("Unable to spawn game: {err}");
                 return;
             }
         };
+        let audit_log = server_config.audit_log.as_ref().and_then(|path| {
+            AuditLog::open(path)
+                .inspect_err(|err| log::error!("Unable to open audit log: {err}"))
+                .ok()
+        });
         let mut update_ticker = tokio::time::interval(Game::update_time_step());
         let mut draw_ticker = tokio::time::interval(Game::draw_time_step());
+        let (dropped_sender, mut dropped_sessions) = mpsc::channel(64);
 
-        let mut tuis: HashMap<PlayerId, Tui> = HashMap::new();
-        let mut idle_warnings: HashMap<PlayerId, u32> = HashMap::new();
+        let mut state = ServerState {
+            game,
+            config: server_config,
+            players,
+            metrics,
+            sessions: HashMap::new(),
+            idle_warnings: HashMap::new(),
+            disconnections: HashMap::new(),
+            broadcast: None,
+            queue: VecDeque::new(),
+            bots: HashMap::new(),
+            announcements: Announcements::default(),
+            audit_log,
+            snapshots: watch::channel(None).0,
+            dropped_sessions: dropped_sender,
+        };
 
         loop {
             select! {
-                Some(tui) = client_receiver.recv() => {
-                    game.add_player(tui.id, tui.username());
-                    tuis.insert(tui.id, tui);
+                Some(tui) = channels.clients.recv() => {
+                    state.start_session(tui).await;
                 }
 
                 _ = update_ticker.tick() => {
+                    let game = &mut state.game;
+                    if game.config().time_trial {
+                        let seed = daily_seed(SystemTime::now());
+                        if game.config().seed != Some(seed) {
+                            log::info!("Starting time trial with daily seed {seed}");
+                            if let Err(err) = game.reseed(seed) {
+                                log::error!("Unable to reseed game: {err}");
+                            }
+                        }
+                    }
+                    let update_start = Instant::now();
                     game.update();
-                }
+                    if let Ok(mut metrics) = state.metrics.lock() {
+                        metrics.observe_update(update_start.elapsed());
+                    }
 
-                _ = draw_ticker.tick() => {
-                    let mut to_remove = vec![];
-                    for (&player_id, tui) in tuis.iter_mut() {
-                        let warning = idle_warnings.get(&player_id).copied();
-                        if let Err(e) = tui.draw(&game, warning) {
-                            log::warn!("draw error for {player_id}: {e}");
-                            to_remove.push(player_id);
-                            continue;
-                        }
-                        if let Err(e) = tui.push_data().await {
-                            log::warn!("push error for {player_id}: {e}");
-                            to_remove.push(player_id);
+                    let events = game.drain_events();
+                    state.announcements.push_events(&events);
+                    if let Some(audit_log) = state.audit_log.as_mut() {
+                        if let Err(err) = audit_log.write(&events) {
+                            log::error!("Unable to write audit log: {err}");
                         }
                     }
-                    for player_id in to_remove {
-                        remove_player(&mut game, &mut tuis, &mut idle_warnings, player_id).await;
+                    state.remove_expired_disconnections().await;
+                    state.admit_queued().await;
+                    state.balance_npcs();
+                }
+
+                _ = draw_ticker.tick() => {
+                    state.publish_snapshot();
+                    state.send_observations();
+
+                    if let Ok(mut metrics) = state.metrics.lock() {
+                        metrics.observe_game(
+                            &state.game,
+                            state.sessions.len() + state.bots.len(),
+                            state.queue.len(),
+                        );
+                        metrics.observe_frame_interval(
+                            state
+                                .sessions
+                                .values()
+                                .map(|session| session.frame_interval())
+                                .max()
+                                .unwrap_or_default(),
+                        );
                     }
                 }
 
-                Some((player_id, event)) = terminal_event_receiver.recv() => {
+                Some((player_id, session_id)) = dropped_sessions.recv() => {
+                    state.drop_session(player_id, session_id).await;
+                }
+
+                Some((command, reply_sender)) = channels.admin.recv() => {
+                    let reply = state.handle_admin_command(command).await;
+                    let _ = reply_sender.send(reply);
+                }
+
+                Some(message) = channels.bots.recv() => {
+                    state.handle_bot_message(message).await;
+                }
+
+                Some((player_id, session_id, event)) = channels.terminal_events.recv() => {
+                    // Ignore leftovers from a session which has been taken over.
+                    if state.sessions.get(&player_id).map(|session| session.session_id()) != Some(session_id) {
+                        state.handle_queued_event(player_id, session_id, event).await;
+                        continue;
+                    }
+
                     match event {
                         TerminalEvent::Key(key_event) => {
-                            idle_warnings.remove(&player_id);
+                            state.idle_warnings.remove(&player_id);
                             if key_event.code == KeyCode::Esc {
-                                remove_player(&mut game, &mut tuis, &mut idle_warnings, player_id).await;
+                                state.remove_player(player_id).await;
+                            } else if key_event.code == CYCLE_COLOR_MODE_KEY {
+                                if let Some(session) = state.sessions.get(&player_id) {
+                                    session.cycle_color_mode();
+                                }
+                            } else if key_event.code == CYCLE_RENDERER_KEY {
+                                if let Some(session) = state.sessions.get(&player_id) {
+                                    session.cycle_renderer();
+                                }
+                            } else if key_event.code == TOGGLE_SIDEBAR_KEY {
+                                if let Some(session) = state.sessions.get(&player_id) {
+                                    session.toggle_sidebar();
+                                }
                             } else if let Some(command) = key_to_command(key_event.code) {
-                                game.handle_command(&command, player_id);
+                                state.game.handle_command(&command, player_id);
                             }
                         }
                         TerminalEvent::Resize(width, height) => {
-                            if let Some(tui) = tuis.get_mut(&player_id) {
-                                let _ = tui.resize(width, height);
+                            if let Some(session) = state.sessions.get(&player_id) {
+                                session.resize(width, height);
                             }
                         }
                         TerminalEvent::IdleWarning(secs) => {
-                            idle_warnings.insert(player_id, secs);
+                            state.idle_warnings.insert(player_id, secs);
                         }
                         TerminalEvent::Quit => {
-                            remove_player(&mut game, &mut tuis, &mut idle_warnings, player_id).await;
+                            state.disconnect_player(player_id).await;
                         }
                         _ => {}
                     }
@@ -87,16 +681,3 @@ pub fn spawn(
         }
     });
 }
-
-async fn remove_player(
-    game: &mut Game,
-    tuis: &mut HashMap<PlayerId, Tui>,
-    idle_warnings: &mut HashMap<PlayerId, u32>,
-    player_id: PlayerId,
-) {
-    game.remove_player(&player_id);
-    idle_warnings.remove(&player_id);
-    if let Some(tui) = tuis.remove(&player_id) {
-        tui.close().await;
-    }
-}
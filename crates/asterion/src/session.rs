@@ -0,0 +1,286 @@
+//! Every session draws its frames in a task of its own, from a snapshot of
+//! the game published by the game task each frame, so that drawing for many
+//! players runs in parallel and does not hold back `Game::update`.
+
+use crate::feed::Announcements;
+use crate::metrics::Metrics;
+use crate::tui::{SessionId, Tui};
+use crate::{AppResult, PlayerId};
+use asterion_core::Game;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::select;
+use tokio::sync::mpsc::{self, Sender, UnboundedSender};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+/// What the sessions draw, published by the game task every frame.
+pub struct Snapshot {
+    pub game: Game,
+    pub announcements: Announcements,
+    pub broadcast: Option<String>,
+    pub idle_warnings: HashMap<PlayerId, u32>,
+    /// Sessions in the game.
+    pub playing: HashSet<SessionId>,
+    /// Sessions waiting for a free spot, in order.
+    pub queue: Vec<SessionId>,
+}
+
+pub type Snapshots = watch::Receiver<Option<Arc<Snapshot>>>;
+
+/// What a session shows of a snapshot.
+#[derive(Debug, PartialEq)]
+enum Screen {
+    Game,
+    /// Where the session stands in the queue, from 1, and how long it is.
+    Queue {
+        position: usize,
+        len: usize,
+    },
+}
+
+impl Snapshot {
+    /// What `session_id` shows, `None` if the snapshot was published before
+    /// the game task got to the session.
+    fn screen(&self, session_id: SessionId) -> Option<Screen> {
+        if self.playing.contains(&session_id) {
+            return Some(Screen::Game);
+        }
+        self.queue
+            .iter()
+            .position(|&id| id == session_id)
+            .map(|index| Screen::Queue {
+                position: index + 1,
+                len: self.queue.len(),
+            })
+    }
+}
+
+#[derive(Debug)]
+enum SessionCommand {
+    Resize(u16, u16),
+    CycleColorMode,
+    CycleRenderer,
+    ToggleSidebar,
+}
+
+/// Draws the frame of `tui` from `snapshot`, if it is due.
+fn draw(tui: &mut Tui, snapshot: &Snapshot, metrics: &Mutex<Metrics>) -> AppResult<()> {
+    if !tui.is_frame_due(Instant::now()) {
+        return Ok(());
+    }
+
+    let Some(screen) = snapshot.screen(tui.session_id()) else {
+        return Ok(());
+    };
+    let warning = snapshot.idle_warnings.get(&tui.id).copied();
+    let draw_start = Instant::now();
+    match screen {
+        Screen::Game => tui.draw(
+            &snapshot.game,
+            warning,
+            snapshot.broadcast.as_deref(),
+            &snapshot.announcements,
+        )?,
+        Screen::Queue { position, len } => tui.draw_queue(position, len, warning)?,
+    }
+    let draw_duration = draw_start.elapsed();
+
+    // Only hands the frame to the writer task, slow clients show up in the
+    // frame interval instead.
+    tui.push_data()?;
+    if let Ok(mut metrics) = metrics.lock() {
+        metrics.observe_draw(tui.session_id(), draw_duration);
+    }
+
+    Ok(())
+}
+
+async fn run(
+    mut tui: Tui,
+    mut snapshots: Snapshots,
+    mut commands: mpsc::UnboundedReceiver<SessionCommand>,
+    frame_interval: Arc<AtomicU64>,
+    metrics: Arc<Mutex<Metrics>>,
+    dropped: Sender<(PlayerId, SessionId)>,
+) {
+    let (player_id, session_id) = (tui.id, tui.session_id());
+    loop {
+        select! {
+            command = commands.recv() => match command {
+                Some(SessionCommand::Resize(width, height)) => {
+                    let _ = tui.resize(width, height);
+                }
+                Some(SessionCommand::CycleColorMode) => tui.cycle_color_mode(),
+                Some(SessionCommand::CycleRenderer) => tui.cycle_renderer(),
+                Some(SessionCommand::ToggleSidebar) => tui.toggle_sidebar(),
+                None => break,
+            },
+
+            changed = snapshots.changed() => {
+                if changed.is_err() {
+                    break;
+                }
+                let Some(snapshot) = snapshots.borrow_and_update().clone() else {
+                    continue;
+                };
+                // Drawing is CPU bound: keep it off the workers running the
+                // game task.
+                let metrics = metrics.clone();
+                let drawn = tokio::task::spawn_blocking(move || {
+                    let result = draw(&mut tui, &snapshot, &metrics);
+                    (tui, result)
+                })
+                .await;
+                let result = match drawn {
+                    Ok((drawn_tui, result)) => {
+                        tui = drawn_tui;
+                        result
+                    }
+                    // The terminal went down with the panic, and is restored
+                    // as its writer is dropped.
+                    Err(err) => {
+                        log::error!("draw panic for {player_id}: {err}");
+                        let _ = dropped.send((player_id, session_id)).await;
+                        return;
+                    }
+                };
+                frame_interval.store(tui.frame_interval().as_millis() as u64, Ordering::Relaxed);
+                if let Err(e) = result {
+                    log::warn!("draw error for {player_id}: {e}");
+                    let _ = dropped.send((player_id, session_id)).await;
+                    break;
+                }
+            }
+        }
+    }
+    if let Ok(mut metrics) = metrics.lock() {
+        metrics.forget_session(&session_id);
+    }
+    tui.close().await;
+}
+
+/// The handle of the game task on a session, whose frames are drawn by a task
+/// of its own.
+#[derive(Debug)]
+pub struct Session {
+    pub id: PlayerId,
+    session_id: SessionId,
+    username: String,
+    frame_interval: Arc<AtomicU64>,
+    commands: UnboundedSender<SessionCommand>,
+    task: JoinHandle<()>,
+}
+
+impl Session {
+    /// Starts drawing `tui` from `snapshots`. Sessions which fail to draw or
+    /// push a frame are reported to `dropped`.
+    pub fn spawn(
+        tui: Tui,
+        snapshots: Snapshots,
+        metrics: Arc<Mutex<Metrics>>,
+        dropped: Sender<(PlayerId, SessionId)>,
+    ) -> Self {
+        let (commands, receiver) = mpsc::unbounded_channel();
+        let frame_interval = Arc::new(AtomicU64::new(tui.frame_interval().as_millis() as u64));
+        Self {
+            id: tui.id,
+            session_id: tui.session_id(),
+            username: tui.username().to_string(),
+            frame_interval: frame_interval.clone(),
+            commands,
+            task: tokio::spawn(run(
+                tui,
+                snapshots,
+                receiver,
+                frame_interval,
+                metrics,
+                dropped,
+            )),
+        }
+    }
+
+    pub fn username(&self) -> &str {
+        self.username.as_str()
+    }
+
+    pub fn session_id(&self) -> SessionId {
+        self.session_id
+    }
+
+    pub fn frame_interval(&self) -> Duration {
+        Duration::from_millis(self.frame_interval.load(Ordering::Relaxed))
+    }
+
+    pub fn resize(&self, width: u16, height: u16) {
+        let _ = self.commands.send(SessionCommand::Resize(width, height));
+    }
+
+    pub fn cycle_color_mode(&self) {
+        let _ = self.commands.send(SessionCommand::CycleColorMode);
+    }
+
+    pub fn cycle_renderer(&self) {
+        let _ = self.commands.send(SessionCommand::CycleRenderer);
+    }
+
+    pub fn toggle_sidebar(&self) {
+        let _ = self.commands.send(SessionCommand::ToggleSidebar);
+    }
+
+    /// Stops drawing, then restores the terminal and closes the SSH channel,
+    /// awaited end-to-end.
+    pub async fn close(self) {
+        drop(self.commands);
+        let _ = self.task.await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Screen, Snapshot};
+    use crate::feed::Announcements;
+    use crate::AppResult;
+    use asterion_core::Game;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_screen() -> AppResult<()> {
+        let (playing, first, second, unknown) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        let snapshot = Snapshot {
+            game: Game::new()?,
+            announcements: Announcements::default(),
+            broadcast: None,
+            idle_warnings: HashMap::new(),
+            playing: [playing].into(),
+            queue: vec![first, second],
+        };
+
+        assert_eq!(snapshot.screen(playing), Some(Screen::Game));
+        assert_eq!(
+            snapshot.screen(first),
+            Some(Screen::Queue {
+                position: 1,
+                len: 2
+            })
+        );
+        assert_eq!(
+            snapshot.screen(second),
+            Some(Screen::Queue {
+                position: 2,
+                len: 2
+            })
+        );
+        assert_eq!(snapshot.screen(unknown), None);
+
+        Ok(())
+    }
+}
@@ -1,13 +1,23 @@
 //! Glue between `frittura-ssh-core`'s `SshGame` trait and asterion's central
-//! game task. Each new SSH session gets a fresh `PlayerId` (UUID) - asterion
-//! is stateless per session and doesn't validate credentials.
+//! game task. Players offering a public key get a `PlayerId` derived from its
+//! fingerprint (see `players`); everyone else gets a fresh `PlayerId` (UUID)
+//! per session. Only banned keys are rejected.
 
-use crate::server_loop;
-use crate::tui::Tui;
-use crate::PlayerId;
+use crate::admin;
+use crate::bots;
+use crate::constants::UI_SCREEN_SIZE;
+use crate::input::InputRateLimiter;
+use crate::metrics::{self, Metrics};
+use crate::players::PlayerRegistry;
+use crate::server_loop::{self, ServerChannels};
+use crate::tui::{SessionId, Tui};
+use crate::utils::sanitize_username;
+use crate::{AppResult, PlayerId, ServerConfig};
+use anyhow::anyhow;
+use asterion_core::GameConfig;
 use frittura_ssh_core::{spawn_event_converter, Credential, SshGame, SshSession, TerminalEvent};
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use uuid::Uuid;
 
@@ -17,19 +27,54 @@ const APP_IDLE_KICK: Duration = Duration::from_secs(60);
 const APP_IDLE_WARNING: Duration = Duration::from_secs(10);
 
 pub struct AsterionGame {
+    players: Arc<Mutex<PlayerRegistry>>,
+    input_rate: u32,
+    metrics: Arc<Mutex<Metrics>>,
     client_sender: mpsc::Sender<Tui>,
-    terminal_event_sender: mpsc::Sender<(PlayerId, TerminalEvent)>,
+    terminal_event_sender: mpsc::Sender<(PlayerId, SessionId, TerminalEvent)>,
 }
 
 impl AsterionGame {
-    pub fn new() -> Arc<Self> {
+    pub fn new(
+        config: GameConfig,
+        server_config: ServerConfig,
+        players: PlayerRegistry,
+    ) -> AppResult<Arc<Self>> {
+        let players = Arc::new(Mutex::new(players));
         let (client_sender, client_receiver) = mpsc::channel(16);
         let (terminal_event_sender, terminal_event_receiver) = mpsc::channel(64);
-        server_loop::spawn(client_receiver, terminal_event_receiver);
-        Arc::new(Self {
+        let (admin_sender, admin_receiver) = mpsc::channel(16);
+        let (bot_sender, bot_receiver) = mpsc::channel(64);
+        let input_rate = server_config.input_rate;
+        if let Some(path) = server_config.admin_socket.clone() {
+            admin::spawn(path, admin_sender)?;
+        }
+        let metrics = Arc::new(Mutex::new(Metrics::default()));
+        if let Some(addr) = server_config.metrics_addr {
+            metrics::spawn(addr, metrics.clone())?;
+        }
+        if let Some(addr) = server_config.bot_addr {
+            bots::spawn(addr, bot_sender, input_rate)?;
+        }
+        server_loop::spawn(
+            config,
+            server_config,
+            players.clone(),
+            metrics.clone(),
+            ServerChannels {
+                clients: client_receiver,
+                terminal_events: terminal_event_receiver,
+                admin: admin_receiver,
+                bots: bot_receiver,
+            },
+        );
+        Ok(Arc::new(Self {
+            players,
+            input_rate,
+            metrics,
             client_sender,
             terminal_event_sender,
-        })
+        }))
     }
 }
 
@@ -41,24 +86,55 @@ impl SshGame for AsterionGame {
 
     async fn authenticate(
         &self,
-        _username: &str,
-        _credential: Credential,
+        username: &str,
+        credential: Credential,
     ) -> anyhow::Result<PlayerId> {
-        // asterion is stateless: every connection is a new player.
-        Ok(Uuid::new_v4())
+        match credential {
+            Credential::PublicKey(key) => {
+                let mut players = self
+                    .players
+                    .lock()
+                    .map_err(|_| anyhow!("Player registry lock poisoned"))?;
+                let player_id = players.identify(username, &key)?;
+                if players.is_banned(&player_id) {
+                    return Err(anyhow!("Player {player_id} is banned"));
+                }
+                Ok(player_id)
+            }
+            // Without a key every connection is a new player.
+            Credential::Password(_) => Ok(Uuid::new_v4()),
+        }
     }
 
     async fn on_session(self: Arc<Self>, session: SshSession<PlayerId>) {
         let SshSession {
             username,
             auth: player_id,
+            term,
             writer,
+            initial_size: (width, height),
             data_rx,
             resize_rx,
             ..
         } = session;
 
-        let tui = match Tui::new(player_id, username, writer) {
+        // Some clients do not advertise a size until the first window change.
+        let size = if width == 0 || height == 0 {
+            UI_SCREEN_SIZE
+        } else {
+            (
+                width.min(u16::MAX as u32) as u16,
+                height.min(u16::MAX as u32) as u16,
+            )
+        };
+        let tui = match Tui::new(
+            player_id,
+            sanitize_username(&username),
+            writer,
+            size,
+            &term,
+            self.metrics.clone(),
+        ) {
             Ok(t) => t,
             Err(e) => {
                 log::error!("Tui init failed for {player_id}: {e}");
@@ -66,6 +142,7 @@ impl SshGame for AsterionGame {
             }
         };
 
+        let session_id = tui.session_id();
         if self.client_sender.send(tui).await.is_err() {
             log::warn!("Game task gone; dropping session for {player_id}");
             return;
@@ -74,11 +151,22 @@ impl SshGame for AsterionGame {
         // Parse inbound bytes + window-changes into a single TerminalEvent
         // stream via the shared core helper, tagged with `player_id` for the
         // central task.
-        let mut events =
-            spawn_event_converter(data_rx, resize_rx, Some(APP_IDLE_KICK), Some(APP_IDLE_WARNING));
+        let mut events = spawn_event_converter(
+            data_rx,
+            resize_rx,
+            Some(APP_IDLE_KICK),
+            Some(APP_IDLE_WARNING),
+        );
         let tev_tx = self.terminal_event_sender.clone();
+        let mut limiter = InputRateLimiter::new(self.input_rate);
         while let Some(ev) = events.recv().await {
-            if tev_tx.send((player_id, ev)).await.is_err() {
+            // Drop input floods here, before they reach the game task.
+            if matches!(ev, TerminalEvent::Key(_) | TerminalEvent::Mouse(_))
+                && !limiter.allow(Instant::now())
+            {
+                continue;
+            }
+            if tev_tx.send((player_id, session_id, ev)).await.is_err() {
                 break;
             }
         }
@@ -0,0 +1,160 @@
+//! User themes, read from a JSON file listing themes such as
+//! `{"name": "Dusk", "base": "Dark", "colors": {"hero": "#268bd2"}}`. Each one
+//! starts from a built-in theme and overrides some of its colors, given as
+//! `#rrggbb` or `#rrggbbaa`. Black and fully transparent colors are reserved
+//! for empty pixels of the room, so only the background and the text can use
+//! them.
+
+use crate::ui::utils::RataColor;
+use crate::AppResult;
+use anyhow::anyhow;
+use asterion_core::{Maze, Theme};
+use image::Rgba;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Debug, Deserialize)]
+struct ThemeConfig {
+    name: String,
+    #[serde(default)]
+    base: Option<String>,
+    #[serde(default)]
+    colors: HashMap<String, String>,
+}
+
+fn parse_color(hex: &str) -> AppResult<Rgba<u8>> {
+    let digits = hex
+        .strip_prefix('#')
+        .filter(|digits| digits.is_ascii() && (digits.len() == 6 || digits.len() == 8))
+        .ok_or(anyhow!(
+            "Invalid color {hex:?}, expected #rrggbb or #rrggbbaa"
+        ))?;
+    let channel = |idx: usize| u8::from_str_radix(&digits[2 * idx..2 * idx + 2], 16);
+    let alpha = if digits.len() == 8 { channel(3)? } else { 255 };
+
+    Ok(Rgba([channel(0)?, channel(1)?, channel(2)?, alpha]))
+}
+
+fn color_mut<'a>(theme: &'a mut Theme, name: &str) -> Option<&'a mut Rgba<u8>> {
+    let color = match name {
+        "background" => &mut theme.background,
+        "text" => &mut theme.text,
+        "hero" => &mut theme.hero,
+        "other_hero" => &mut theme.other_hero,
+        "minotaur" => &mut theme.minotaur,
+        "chasing_minotaur" => &mut theme.chasing_minotaur,
+        "power_up" => &mut theme.power_up,
+        "ghost" => &mut theme.ghost,
+        "shrine" => &mut theme.shrine,
+        "explored" => &mut theme.explored,
+        "entrance" => &mut theme.entrance,
+        "exit" => &mut theme.exit,
+        "first_walls" => &mut theme.first_walls,
+        "last_walls" => &mut theme.last_walls,
+        "ahead" => &mut theme.ahead,
+        "behind" => &mut theme.behind,
+        _ => return None,
+    };
+    Some(color)
+}
+
+fn to_theme(config: ThemeConfig) -> AppResult<Theme> {
+    let ThemeConfig { name, base, colors } = config;
+    let mut theme = match base.as_deref() {
+        None => Theme::default(),
+        Some(base) => Theme::built_in()
+            .into_iter()
+            .find(|theme| theme.name.eq_ignore_ascii_case(base))
+            .ok_or(anyhow!("Unknown base theme {base:?} for {name}"))?,
+    };
+
+    for (color_name, hex) in colors {
+        let color = color_mut(&mut theme, &color_name)
+            .ok_or(anyhow!("Unknown color {color_name:?} in theme {name}"))?;
+        let parsed = parse_color(&hex)?;
+        if !matches!(color_name.as_str(), "background" | "text")
+            && parsed.is_transparent(Maze::background_color())
+        {
+            return Err(anyhow!(
+                "Color {hex:?} of {color_name:?} in theme {name} is reserved for empty pixels"
+            ));
+        }
+        *color = parsed;
+    }
+    theme.name = name;
+
+    Ok(theme)
+}
+
+/// Loads the themes in `path`, if it exists.
+pub fn load(path: &Path) -> AppResult<Vec<Theme>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+
+    let configs: Vec<ThemeConfig> = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    let themes = configs
+        .into_iter()
+        .map(to_theme)
+        .collect::<AppResult<Vec<Theme>>>()?;
+    log::info!("Loaded {} themes from {}", themes.len(), path.display());
+
+    Ok(themes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_color, to_theme, ThemeConfig};
+    use crate::AppResult;
+    use asterion_core::Theme;
+    use image::Rgba;
+    use std::collections::HashMap;
+
+    fn config(base: Option<&str>, colors: &[(&str, &str)]) -> ThemeConfig {
+        ThemeConfig {
+            name: "Dusk".to_string(),
+            base: base.map(str::to_string),
+            colors: colors
+                .iter()
+                .map(|(name, hex)| (name.to_string(), hex.to_string()))
+                .collect::<HashMap<_, _>>(),
+        }
+    }
+
+    #[test]
+    fn test_parse_color() -> AppResult<()> {
+        assert_eq!(parse_color("#268bd2")?, Rgba([0x26, 0x8b, 0xd2, 255]));
+        assert_eq!(parse_color("#268BD280")?, Rgba([0x26, 0x8b, 0xd2, 0x80]));
+        for hex in ["268bd2", "#268bd", "#268bd2f", "#268bdz", "#ééé"] {
+            assert!(parse_color(hex).is_err(), "{hex}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_theme() -> AppResult<()> {
+        let theme = to_theme(config(
+            Some("light"),
+            &[("hero", "#268bd2"), ("text", "#000000")],
+        ))?;
+        let light = Theme::built_in()
+            .into_iter()
+            .find(|theme| theme.name == "Light")
+            .unwrap();
+        assert_eq!(theme.name, "Dusk");
+        assert_eq!(theme.hero, Rgba([0x26, 0x8b, 0xd2, 255]));
+        assert_eq!(theme.text, Rgba([0, 0, 0, 255]));
+        assert_eq!(theme.minotaur, light.minotaur);
+
+        assert!(to_theme(config(None, &[("background", "#000000")])).is_ok());
+        // Reserved for empty pixels.
+        assert!(to_theme(config(None, &[("hero", "#000000")])).is_err());
+        assert!(to_theme(config(None, &[("exit", "#268bd200")])).is_err());
+        assert!(to_theme(config(None, &[("wall", "#268bd2")])).is_err());
+        assert!(to_theme(config(Some("sepia"), &[])).is_err());
+
+        Ok(())
+    }
+}
@@ -1,28 +1,72 @@
-use crate::constants::UI_SCREEN_SIZE;
+use crate::client_writer::{ClientWriter, FrameRate};
+use crate::feed::Announcements;
+use crate::metrics::Metrics;
 use crate::ui;
+use crate::ui::color_mode::ColorMode;
+use crate::ui::renderer::Renderer;
 use crate::AppResult;
 use crate::PlayerId;
-use asterion_core::Game;
+use asterion_core::{Camera, Game};
 use frittura_ssh_core::{idle_warning_text, SshWriterProxy};
+use ratatui::buffer::Buffer;
 use ratatui::crossterm::cursor::Hide;
 use ratatui::crossterm::event::EnableMouseCapture;
 use ratatui::crossterm::terminal::Clear;
 use ratatui::crossterm::terminal::EnterAlternateScreen;
 use ratatui::layout::Rect;
 use ratatui::prelude::CrosstermBackend;
-use ratatui::style::Style;
+use ratatui::style::{Style, Stylize};
+use ratatui::text::Line;
 use ratatui::widgets::{Block, Clear as ClearWidget, Paragraph};
+use ratatui::Frame;
 use ratatui::Terminal;
 use ratatui::TerminalOptions;
 use ratatui::Viewport;
-use std::time::Instant;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Tells apart successive SSH sessions of the same player.
+pub type SessionId = Uuid;
 
 #[derive(Debug)]
 pub struct Tui {
     pub id: PlayerId,
+    session_id: SessionId,
     username: String,
     start_instant: Instant,
-    terminal: Terminal<CrosstermBackend<SshWriterProxy>>,
+    camera: Camera,
+    color_mode: ColorMode,
+    renderer: Renderer,
+    /// Whether the sidebar is shown over the room when the terminal is too
+    /// narrow to fit it beside it.
+    sidebar_overlay: bool,
+    frame_rate: FrameRate,
+    /// The last frame pushed, to skip the ones which do not change it.
+    last_frame: Option<Buffer>,
+    terminal: Terminal<CrosstermBackend<ClientWriter>>,
+}
+
+/// Draws a frame, dropping its bytes when it looks like the last one.
+fn draw_frame<F: FnOnce(&mut Frame)>(
+    terminal: &mut Terminal<CrosstermBackend<ClientWriter>>,
+    last_frame: &mut Option<Buffer>,
+    render: F,
+) -> AppResult<()> {
+    let pending = terminal.backend().writer().pending();
+    let unchanged = {
+        let frame = terminal.draw(render)?;
+        let unchanged = last_frame.as_ref() == Some(frame.buffer);
+        if !unchanged {
+            *last_frame = Some(frame.buffer.clone());
+        }
+        unchanged
+    };
+    // Only the cursor is hidden again when no cell changed.
+    if unchanged {
+        terminal.backend_mut().writer_mut().truncate(pending);
+    }
+    Ok(())
 }
 
 impl Tui {
@@ -41,61 +85,170 @@ impl Tui {
         self.username.as_str()
     }
 
-    pub fn new(id: PlayerId, username: String, writer: SshWriterProxy) -> AppResult<Self> {
-        let backend = CrosstermBackend::new(writer);
+    pub fn session_id(&self) -> SessionId {
+        self.session_id
+    }
+
+    /// `size` and `term` are the ones advertised at pty request time, later
+    /// window changes go through [`Tui::resize`].
+    pub fn new(
+        id: PlayerId,
+        username: String,
+        writer: SshWriterProxy,
+        size: (u16, u16),
+        term: &str,
+        metrics: Arc<Mutex<Metrics>>,
+    ) -> AppResult<Self> {
+        let backend = CrosstermBackend::new(ClientWriter::new(writer, metrics));
         let opts = TerminalOptions {
             viewport: Viewport::Fixed(Rect {
                 x: 0,
                 y: 0,
-                width: UI_SCREEN_SIZE.0,
-                height: UI_SCREEN_SIZE.1,
+                width: size.0,
+                height: size.1,
             }),
         };
         let terminal = Terminal::with_options(backend, opts)?;
         let mut tui = Self {
             id,
+            session_id: Uuid::new_v4(),
             username,
             start_instant: Instant::now(),
+            camera: Camera::default(),
+            color_mode: ColorMode::detect(term, None),
+            renderer: Renderer::default(),
+            sidebar_overlay: false,
+            frame_rate: FrameRate::default(),
+            last_frame: None,
             terminal,
         };
         tui.init()?;
         Ok(tui)
     }
 
-    pub fn draw(&mut self, game: &Game, idle_warning: Option<u32>) -> AppResult<()> {
+    pub fn cycle_color_mode(&mut self) {
+        self.color_mode = self.color_mode.next();
+    }
+
+    pub fn cycle_renderer(&mut self) {
+        self.renderer = self.renderer.next();
+    }
+
+    pub fn toggle_sidebar(&mut self) {
+        self.sidebar_overlay = !self.sidebar_overlay;
+    }
+
+    /// Whether it is time for the next frame of the client. A client still
+    /// receiving its previous frames gets them less often.
+    pub fn is_frame_due(&mut self, now: Instant) -> bool {
+        if !self.frame_rate.is_due(now) {
+            return false;
+        }
+        if !self.terminal.backend().writer().is_idle() {
+            self.frame_rate.slow_down();
+            return false;
+        }
+        true
+    }
+
+    pub fn frame_interval(&self) -> Duration {
+        self.frame_rate.interval()
+    }
+
+    pub fn draw(
+        &mut self,
+        game: &Game,
+        idle_warning: Option<u32>,
+        broadcast: Option<&str>,
+        announcements: &Announcements,
+    ) -> AppResult<()> {
         let id = self.id;
         let start = self.start_instant;
-        self.terminal.draw(|frame| {
-            ui::ui::render(frame, game, id, start).expect("Error while rendering game.");
-            if let Some(secs) = idle_warning {
+        let camera = &mut self.camera;
+        let color_mode = self.color_mode;
+        let renderer = self.renderer;
+        let sidebar_overlay = self.sidebar_overlay;
+        draw_frame(&mut self.terminal, &mut self.last_frame, |frame| {
+            ui::ui::render(
+                frame,
+                game,
+                id,
+                start,
+                announcements,
+                camera,
+                renderer,
+                sidebar_overlay,
+            )
+            .expect("Error while rendering game.");
+            if let Some(message) = broadcast {
                 let area = frame.area();
-                let banner_w: u16 = 50;
-                let banner_h: u16 = 3;
+                let banner_w = (message.chars().count() as u16 + 4).min(area.width);
                 let banner = Rect {
                     x: area.x + area.width.saturating_sub(banner_w) / 2,
-                    y: area.y + area.height.saturating_sub(banner_h) / 2,
-                    width: banner_w.min(area.width),
-                    height: banner_h.min(area.height),
+                    y: area.y,
+                    width: banner_w,
+                    height: 3.min(area.height),
                 };
                 frame.render_widget(ClearWidget, banner);
                 frame.render_widget(
-                    Paragraph::new(idle_warning_text(secs))
+                    Paragraph::new(message)
                         .centered()
-                        .style(Style::new().red().bold())
+                        .style(Style::new().yellow().bold())
                         .block(Block::bordered()),
                     banner,
                 );
             }
+            if let Some(secs) = idle_warning {
+                render_idle_warning(frame, secs);
+            }
+            color_mode.apply_for_player(frame.buffer_mut(), game, id);
         })?;
         Ok(())
     }
 
-    pub async fn push_data(&mut self) -> AppResult<()> {
-        self.terminal.backend_mut().writer_mut().send().await?;
+    /// Shown instead of the game while waiting for a free spot.
+    pub fn draw_queue(
+        &mut self,
+        position: usize,
+        queue_len: usize,
+        idle_warning: Option<u32>,
+    ) -> AppResult<()> {
+        draw_frame(&mut self.terminal, &mut self.last_frame, |frame| {
+            let area = frame.area();
+            let text = vec![
+                Line::from("The labyrinth is full".bold()),
+                Line::default(),
+                Line::from(format!(
+                    "You are number {position} of {queue_len} in the queue"
+                )),
+                Line::from("Press any key now and then to keep your spot, Esc to leave"),
+            ];
+            let height = (text.len() as u16 + 2).min(area.height);
+            let queue_area = Rect {
+                x: area.x,
+                y: area.y + area.height.saturating_sub(height) / 2,
+                width: area.width,
+                height,
+            };
+            frame.render_widget(Paragraph::new(text).centered(), queue_area);
+            if let Some(secs) = idle_warning {
+                render_idle_warning(frame, secs);
+            }
+        })?;
+        Ok(())
+    }
+
+    /// Hands the frame to the writer task of the client, without waiting for
+    /// it to be sent.
+    pub fn push_data(&mut self) -> AppResult<()> {
+        self.terminal.backend_mut().writer_mut().push()?;
+        self.frame_rate.record_frame(Instant::now());
         Ok(())
     }
 
     pub fn resize(&mut self, width: u16, height: u16) -> AppResult<()> {
+        // Resizing clears the screen, so the next frame is drawn in full.
+        self.last_frame = None;
         self.terminal.resize(Rect {
             x: 0,
             y: 0,
@@ -107,6 +260,26 @@ impl Tui {
 
     /// Restore the terminal and close the SSH channel, awaited end-to-end.
     pub async fn close(mut self) {
-        self.terminal.backend_mut().writer_mut().send_and_close().await;
+        self.terminal.backend_mut().writer_mut().close().await;
     }
 }
+
+fn render_idle_warning(frame: &mut Frame, secs: u32) {
+    let area = frame.area();
+    let banner_w: u16 = 50;
+    let banner_h: u16 = 3;
+    let banner = Rect {
+        x: area.x + area.width.saturating_sub(banner_w) / 2,
+        y: area.y + area.height.saturating_sub(banner_h) / 2,
+        width: banner_w.min(area.width),
+        height: banner_h.min(area.height),
+    };
+    frame.render_widget(ClearWidget, banner);
+    frame.render_widget(
+        Paragraph::new(idle_warning_text(secs))
+            .centered()
+            .style(Style::new().red().bold())
+            .block(Block::bordered()),
+        banner,
+    );
+}
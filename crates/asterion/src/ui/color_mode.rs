@@ -0,0 +1,257 @@
+//! Fallbacks for terminals without truecolor. Frames are always rendered with
+//! RGB colors, then the buffer is converted to the color mode of the client:
+//! the nearest colors of the 256 or 16 color palettes, or plain ASCII glyphs.
+
+use super::utils::RataColor;
+use asterion_core::{Game, PlayerId, Theme, UiOptions};
+use ratatui::{buffer::Buffer, style::Color};
+use strum_macros::Display;
+
+#[derive(Debug, Clone, Copy, Default, Display, PartialEq)]
+pub enum ColorMode {
+    #[default]
+    TrueColor,
+    Ansi256,
+    Ansi16,
+    Ascii,
+}
+
+/// Terminals known to support truecolor even when `TERM` does not say so.
+const TRUECOLOR_TERMS: [&str; 6] = ["kitty", "alacritty", "wezterm", "foot", "ghostty", "iterm"];
+
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// From dark to bright, for the pixels which are not a known entity.
+const ASCII_RAMP: [char; 6] = [' ', '.', ':', ';', '=', '#'];
+
+fn distance((r, g, b): (u8, u8, u8), (or, og, ob): (u8, u8, u8)) -> u32 {
+    let d = |c: u8, o: u8| (c as i32 - o as i32).pow(2) as u32;
+    d(r, or) + d(g, og) + d(b, ob)
+}
+
+fn nearest_256(rgb: (u8, u8, u8)) -> Color {
+    let level = |c: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&idx| (CUBE_LEVELS[idx] as i32 - c as i32).abs())
+            .unwrap_or_default()
+    };
+    let (r, g, b) = (level(rgb.0), level(rgb.1), level(rgb.2));
+    let cube = (CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]);
+
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let gray_idx = (average.saturating_sub(8) / 10).min(23) as u8;
+    let gray = 8 + 10 * gray_idx;
+
+    if distance(rgb, (gray, gray, gray)) < distance(rgb, cube) {
+        Color::Indexed(232 + gray_idx)
+    } else {
+        Color::Indexed(16 + 36 * r as u8 + 6 * g as u8 + b as u8)
+    }
+}
+
+fn nearest_16(rgb: (u8, u8, u8)) -> Color {
+    ANSI16
+        .iter()
+        .min_by_key(|(_, palette_rgb)| distance(rgb, *palette_rgb))
+        .map(|(color, _)| *color)
+        .unwrap_or_default()
+}
+
+fn luminance((r, g, b): (u8, u8, u8)) -> u32 {
+    (299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000
+}
+
+fn rgb(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Rgb(r, g, b) => Some((r, g, b)),
+        _ => None,
+    }
+}
+
+/// A glyph for a block cell, from its top (foreground) and bottom
+/// (background) colors.
+fn ascii_glyph(fg: Color, bg: Color, theme: &Theme) -> char {
+    let entities = [
+        (theme.hero, '@'),
+        (theme.other_hero, 'h'),
+        (theme.chasing_minotaur, 'M'),
+        (theme.minotaur, 'm'),
+        (theme.power_up, '+'),
+        (theme.ghost, 'g'),
+        (theme.entrance, '<'),
+        (theme.exit, '>'),
+    ];
+    let colors = [rgb(fg), rgb(bg)].into_iter().flatten();
+
+    for color in colors.clone() {
+        for (entity, glyph) in entities.iter() {
+            let matches = [entity.to_color(), entity.on(theme.background)]
+                .into_iter()
+                .filter_map(rgb)
+                .any(|entity| distance(color, entity) <= 12);
+            if matches {
+                return *glyph;
+            }
+        }
+    }
+
+    let brightest = colors.map(luminance).max().unwrap_or_default();
+    ASCII_RAMP[(brightest as usize * ASCII_RAMP.len() / 256).min(ASCII_RAMP.len() - 1)]
+}
+
+impl ColorMode {
+    /// Guesses the color mode of a terminal from its `TERM` and `COLORTERM`
+    /// variables. `COLORTERM` is `None` when unknown, as SSH clients do not
+    /// send it: 256 color terminals then keep to their palette, as tmux and
+    /// Terminal.app do not all render truecolor.
+    pub fn detect(term: &str, colorterm: Option<&str>) -> Self {
+        let term = term.to_lowercase();
+        if matches!(colorterm, Some("truecolor" | "24bit"))
+            || term.ends_with("-direct")
+            || TRUECOLOR_TERMS.iter().any(|name| term.contains(name))
+        {
+            Self::TrueColor
+        } else if term.contains("256color") {
+            Self::Ansi256
+        } else if term.is_empty() || term == "dumb" || term.starts_with("vt") {
+            Self::Ascii
+        } else {
+            Self::Ansi16
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Self::TrueColor => Self::Ansi256,
+            Self::Ansi256 => Self::Ansi16,
+            Self::Ansi16 => Self::Ascii,
+            Self::Ascii => Self::TrueColor,
+        }
+    }
+
+    fn convert(&self, color: Color) -> Color {
+        match (self, rgb(color)) {
+            (Self::Ansi256, Some(rgb)) => nearest_256(rgb),
+            (Self::Ansi16, Some(rgb)) => nearest_16(rgb),
+            (Self::Ascii, _) => Color::Reset,
+            _ => color,
+        }
+    }
+
+    /// Converts a rendered frame to this color mode.
+    pub fn apply(&self, buffer: &mut Buffer, theme: &Theme) {
+        if *self == Self::TrueColor {
+            return;
+        }
+
+        for cell in buffer.content.iter_mut() {
+            if *self == Self::Ascii {
+                let glyph = match cell.symbol().chars().next().unwrap_or(' ') {
+                    c if c.is_ascii() => None,
+                    '═' | '─' => Some('-'),
+                    '║' | '│' => Some('|'),
+                    '\u{2500}'..='\u{257F}' => Some('+'),
+                    // Blocks, sextants and Braille patterns.
+                    '\u{2580}'..='\u{259F}'
+                    | '\u{1FB00}'..='\u{1FB3B}'
+                    | '\u{2800}'..='\u{28FF}' => Some(ascii_glyph(cell.fg, cell.bg, theme)),
+                    '·' => Some('.'),
+                    '←' => Some('<'),
+                    '→' => Some('>'),
+                    _ => None,
+                };
+                if let Some(glyph) = glyph {
+                    cell.set_char(glyph);
+                }
+            }
+            cell.fg = self.convert(cell.fg);
+            cell.bg = self.convert(cell.bg);
+        }
+    }
+
+    /// Converts a frame rendered for `player_id`, with the glyphs of their
+    /// theme.
+    pub fn apply_for_player(&self, buffer: &mut Buffer, game: &Game, player_id: PlayerId) {
+        let ui_options = game
+            .get_hero(&player_id)
+            .map_or(UiOptions::Dark, |hero| hero.ui_options());
+        self.apply(buffer, game.theme(ui_options));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ColorMode;
+    use asterion_core::Theme;
+    use ratatui::{buffer::Buffer, layout::Rect, style::Color};
+
+    #[test]
+    fn test_color_modes() {
+        assert_eq!(
+            ColorMode::detect("xterm-256color", Some("truecolor")),
+            ColorMode::TrueColor
+        );
+        assert_eq!(
+            ColorMode::detect("xterm-256color", None),
+            ColorMode::Ansi256
+        );
+        assert_eq!(
+            ColorMode::detect("xterm-256color", Some("")),
+            ColorMode::Ansi256
+        );
+        assert_eq!(ColorMode::detect("tmux-256color", None), ColorMode::Ansi256);
+        assert_eq!(
+            ColorMode::detect("screen-256color", None),
+            ColorMode::Ansi256
+        );
+        assert_eq!(ColorMode::detect("xterm", None), ColorMode::Ansi16);
+        assert_eq!(ColorMode::detect("dumb", None), ColorMode::Ascii);
+
+        let theme = Theme::dark();
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 3, 1));
+        buffer[(0, 0)]
+            .set_char('▀')
+            .set_fg(Color::Rgb(35, 35, 255))
+            .set_bg(Color::Rgb(255, 0, 0));
+        buffer[(1, 0)]
+            .set_char('═')
+            .set_fg(Color::Rgb(255, 255, 255));
+        buffer[(2, 0)]
+            .set_char('▀')
+            .set_fg(Color::Rgb(250, 250, 250));
+
+        let mut ansi256 = buffer.clone();
+        ColorMode::Ansi256.apply(&mut ansi256, &theme);
+        assert_eq!(ansi256[(0, 0)].bg, Color::Indexed(196));
+        assert_eq!(ansi256[(1, 0)].fg, Color::Indexed(231));
+
+        let mut ansi16 = buffer.clone();
+        ColorMode::Ansi16.apply(&mut ansi16, &theme);
+        assert_eq!(ansi16[(0, 0)].bg, Color::LightRed);
+
+        ColorMode::Ascii.apply(&mut buffer, &theme);
+        assert_eq!(buffer[(0, 0)].symbol(), "@");
+        assert_eq!(buffer[(1, 0)].symbol(), "-");
+        assert_eq!(buffer[(2, 0)].symbol(), "#");
+        assert_eq!(buffer[(0, 0)].fg, Color::Reset);
+    }
+}
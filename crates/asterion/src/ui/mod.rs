@@ -1,3 +1,6 @@
+pub mod color_mode;
+pub mod renderer;
+pub mod tiles;
 #[allow(clippy::module_inception)]
 pub mod ui;
 pub(crate) mod utils;
@@ -0,0 +1,203 @@
+//! Glyphs drawing several pixels of an image in one character of the
+//! terminal. A character only has two colors, so the pixels of each one are
+//! split in two groups, drawn with the average color of each group.
+
+use ratatui::style::Color;
+use strum_macros::Display;
+
+#[derive(Debug, Clone, Copy, Default, Display, PartialEq)]
+pub enum Renderer {
+    /// 1x2 pixels, the most widely supported.
+    #[default]
+    HalfBlock,
+    /// 2x2 pixels, with the quadrant blocks.
+    Quadrant,
+    /// 2x3 pixels, with the sextants of Unicode 13, which not every font has.
+    Sextant,
+    /// 2x4 dots, drawn smaller than the pixels of the other renderers.
+    Braille,
+}
+
+const HALF_BLOCKS: [char; 4] = [' ', '▀', '▄', '█'];
+
+const QUADRANTS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+
+/// Bits of the Braille dots, by pixel in row-major order.
+const BRAILLE_DOTS: [u32; 8] = [0x01, 0x08, 0x02, 0x10, 0x04, 0x20, 0x40, 0x80];
+
+fn distance(a: [u8; 3], b: [u8; 3]) -> u32 {
+    a.iter()
+        .zip(b.iter())
+        .map(|(&c, &o)| (c as i32 - o as i32).pow(2) as u32)
+        .sum()
+}
+
+fn average(colors: impl Iterator<Item = [u8; 3]>) -> Color {
+    let (mut sum, mut count) = ([0u32; 3], 0);
+    for color in colors {
+        for (channel, value) in sum.iter_mut().zip(color) {
+            *channel += value as u32;
+        }
+        count += 1;
+    }
+    let [r, g, b] = sum.map(|channel| (channel / count.max(1)) as u8);
+    Color::Rgb(r, g, b)
+}
+
+impl Renderer {
+    pub fn next(&self) -> Self {
+        match self {
+            Self::HalfBlock => Self::Quadrant,
+            Self::Quadrant => Self::Sextant,
+            Self::Sextant => Self::Braille,
+            Self::Braille => Self::HalfBlock,
+        }
+    }
+
+    /// Columns and rows of pixels held by a character.
+    pub fn cell_size(&self) -> (u32, u32) {
+        match self {
+            Self::HalfBlock => (1, 2),
+            Self::Quadrant => (2, 2),
+            Self::Sextant => (2, 3),
+            Self::Braille => (2, 4),
+        }
+    }
+
+    /// The glyph drawing in its foreground the pixels set in `mask`, by bit
+    /// in row-major order.
+    fn glyph(&self, mask: u32) -> char {
+        match self {
+            Self::HalfBlock => HALF_BLOCKS[mask as usize],
+            Self::Quadrant => QUADRANTS[mask as usize],
+            Self::Sextant => match mask {
+                0 => ' ',
+                21 => '▌',
+                42 => '▐',
+                63 => '█',
+                // The sextants skip the patterns of the half and full blocks.
+                _ => {
+                    let skipped = (mask > 21) as u32 + (mask > 42) as u32;
+                    char::from_u32(0x1FB00 + mask - 1 - skipped).unwrap_or('?')
+                }
+            },
+            Self::Braille => {
+                let dots = (0..8)
+                    .filter(|bit| mask & (1 << bit) != 0)
+                    .fold(0, |dots, bit| dots | BRAILLE_DOTS[bit]);
+                char::from_u32(0x2800 + dots).unwrap_or('?')
+            }
+        }
+    }
+
+    /// Glyph, foreground and background of a character from its pixels in
+    /// row-major order, blended on `background`. Transparent pixels are
+    /// `None` and leave the background of the character unset when they
+    /// make up a group of their own.
+    pub fn cell(
+        &self,
+        pixels: &[Option<[u8; 3]>],
+        background: [u8; 3],
+    ) -> (char, Option<Color>, Option<Color>) {
+        let values = pixels
+            .iter()
+            .map(|pixel| pixel.unwrap_or(background))
+            .collect::<Vec<[u8; 3]>>();
+
+        // Split the pixels around the two most different ones.
+        let mut seeds = (0, 0);
+        let mut max_distance = 0;
+        for i in 0..values.len() {
+            for j in i + 1..values.len() {
+                let d = distance(values[i], values[j]);
+                if d > max_distance {
+                    max_distance = d;
+                    seeds = (i, j);
+                }
+            }
+        }
+        let mut mask = (0..values.len())
+            .filter(|&idx| {
+                distance(values[idx], values[seeds.0]) <= distance(values[idx], values[seeds.1])
+            })
+            .fold(0, |mask, idx| mask | 1 << idx);
+
+        let full = (1 << values.len()) - 1;
+        let transparent = |mask: u32| {
+            (0..pixels.len()).all(|idx| mask & (1 << idx) == 0 || pixels[idx].is_none())
+        };
+        if transparent(mask) {
+            mask ^= full;
+        }
+        if mask == 0 || transparent(mask) {
+            return (' ', None, None);
+        }
+
+        let group = |mask: u32| {
+            values
+                .iter()
+                .enumerate()
+                .filter(move |(idx, _)| mask & (1 << idx) != 0)
+                .map(|(_, &value)| value)
+        };
+        let fg = average(group(mask));
+        let bg = if transparent(full ^ mask) {
+            None
+        } else {
+            Some(average(group(full ^ mask)))
+        };
+
+        (self.glyph(mask), Some(fg), bg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Renderer;
+    use ratatui::style::Color;
+
+    #[test]
+    fn test_cells() {
+        let black = [0, 0, 0];
+        let red = Some([255, 0, 0]);
+        let blue = Some([0, 0, 200]);
+
+        assert_eq!(
+            Renderer::HalfBlock.cell(&[red, None], black),
+            ('▀', Some(Color::Rgb(255, 0, 0)), None)
+        );
+        assert_eq!(
+            Renderer::HalfBlock.cell(&[None, blue], black),
+            ('▄', Some(Color::Rgb(0, 0, 200)), None)
+        );
+        assert_eq!(
+            Renderer::Quadrant.cell(&[None; 4], black),
+            (' ', None, None)
+        );
+
+        // Two colors and a transparent pixel: the red ones are split from the
+        // blue one and the background, blended in the background color.
+        assert_eq!(
+            Renderer::Quadrant.cell(&[red, None, red, blue], black),
+            (
+                '▌',
+                Some(Color::Rgb(255, 0, 0)),
+                Some(Color::Rgb(0, 0, 100))
+            )
+        );
+        assert_eq!(
+            Renderer::Sextant
+                .cell(&[red, None, None, None, None, red], black)
+                .0,
+            '\u{1FB1F}'
+        );
+        assert_eq!(
+            Renderer::Braille
+                .cell(&[None, None, None, None, None, None, None, red], black)
+                .0,
+            '⢀'
+        );
+    }
+}
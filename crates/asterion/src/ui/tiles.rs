@@ -0,0 +1,209 @@
+//! Renderers of the tiles seen by a hero: one pixel each with the glyphs of
+//! the floor, entrance and exit drawn over them in the terminal, or scaled up
+//! to be saved as PNG.
+
+use super::renderer::Renderer;
+use super::utils::{cells_to_lines, RataColor};
+use asterion_core::{Grid, Maze, Theme, Tile, TileMap};
+use image::{imageops, Rgba, RgbaImage};
+use ratatui::text::Line;
+use std::collections::HashMap;
+
+/// Color of a tile on the background of `theme`, `None` for the floor and
+/// what the hero does not see.
+fn color(tile: Tile, theme: &Theme, walls: Rgba<u8>) -> Option<[u8; 3]> {
+    let Rgba([r, g, b, _]) = walls;
+    let color = match tile {
+        Tile::Unknown
+        | Tile::FoggedFloor
+        | Tile::Floor { .. }
+        | Tile::Entrance { .. }
+        | Tile::Exit { .. } => return None,
+        Tile::Wall { visibility } => Rgba([r, g, b, visibility]),
+        Tile::Hero => theme.hero,
+        Tile::OtherHero => theme.other_hero,
+        Tile::Minotaur => theme.minotaur,
+        Tile::ChasingMinotaur => theme.chasing_minotaur,
+        Tile::PowerUp => theme.power_up,
+        Tile::Ghost => theme.ghost,
+        Tile::Shrine => theme.shrine,
+    };
+    Some(color.rgb_on(theme.background))
+}
+
+/// Visibility of the glyph drawn over a tile, `None` for the tiles drawn as
+/// pixels of their own color.
+fn visibility(tile: Tile) -> Option<u8> {
+    match tile {
+        Tile::Wall { visibility }
+        | Tile::Floor { visibility }
+        | Tile::Entrance { visibility }
+        | Tile::Exit { visibility } => Some(visibility),
+        Tile::Unknown | Tile::FoggedFloor => Some(0),
+        _ => None,
+    }
+}
+
+/// The numbers of the rooms the entrance and exit lead to, next to them and
+/// one char per character of the terminal, `cell_width` tiles wide.
+fn labels(tiles: &TileMap, cell_width: u32) -> HashMap<(u32, u32), char> {
+    let mut labels = HashMap::new();
+    let mut label = |x: Option<u32>, y: u32, c: char| {
+        // Heros and minotaurs are not hidden by the labels.
+        if let Some(x) = x.filter(|&x| tiles.get(x, y).and_then(visibility).is_some()) {
+            labels.insert((x, y), c);
+        }
+    };
+
+    for ((x, y), tile) in tiles.iter() {
+        match tile {
+            Tile::Entrance { .. } => {
+                for (idx, c) in tiles.maze_id().to_string().chars().enumerate() {
+                    label(Some(x + (idx as u32 + 1) * cell_width), y, c);
+                }
+            }
+            Tile::Exit { .. } => {
+                for (idx, c) in (tiles.maze_id() + 2).to_string().chars().rev().enumerate() {
+                    label(x.checked_sub((idx as u32 + 1) * cell_width), y, c);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    labels
+}
+
+/// The lines of characters drawing the tiles with `renderer`. The entrance,
+/// exit and their labels are always drawn, while the dots marking the floor
+/// in view need all the other pixels of their character to be empty.
+pub fn to_lines<'a>(tiles: &TileMap, theme: &Theme, renderer: Renderer) -> Vec<Line<'a>> {
+    let walls = Maze::color(tiles.maze_id(), theme);
+    let labels = labels(tiles, renderer.cell_size().0);
+    let tile = |(x, y): (u32, u32)| tiles.get(x, y).unwrap_or_default();
+    let text = |visibility: u8| {
+        let Rgba([r, g, b, _]) = theme.text;
+        Rgba([r, g, b, visibility]).on(theme.background)
+    };
+
+    let glyph = |positions: &[(u32, u32)]| {
+        let mark = positions.iter().find_map(|&position| {
+            let c = match tile(position) {
+                Tile::Entrance { .. } => '←',
+                Tile::Exit { .. } => '→',
+                _ => *labels.get(&position)?,
+            };
+            Some((c, text(visibility(tile(position))?)))
+        });
+        if mark.is_some() {
+            return mark;
+        }
+
+        if positions
+            .iter()
+            .any(|&position| color(tile(position), theme, walls).is_some())
+        {
+            return None;
+        }
+        positions
+            .iter()
+            .filter_map(|&position| match tile(position) {
+                Tile::Floor { visibility } => Some(visibility),
+                _ => None,
+            })
+            .max()
+            .map(|visibility| ('·', text(visibility)))
+    };
+
+    cells_to_lines(
+        tiles.dimensions(),
+        |x, y| color(tile((x, y)), theme, walls),
+        glyph,
+        theme.background,
+        renderer,
+    )
+}
+
+/// The lines of characters drawing a grid of colors like the minimap.
+pub fn grid_to_lines<'a>(
+    grid: &Grid<Rgba<u8>>,
+    theme: &Theme,
+    renderer: Renderer,
+) -> Vec<Line<'a>> {
+    cells_to_lines(
+        grid.dimensions(),
+        |x, y| {
+            grid.get(x, y)
+                .filter(|pixel| !pixel.is_transparent(Maze::background_color()))
+                .map(|pixel| pixel.rgb_on(theme.background))
+        },
+        |_| None,
+        theme.background,
+        renderer,
+    )
+}
+
+/// `scale`x`scale` pixels per tile, on the background of `theme`.
+pub fn to_png_image(tiles: &TileMap, theme: &Theme, scale: u32) -> RgbaImage {
+    let walls = Maze::color(tiles.maze_id(), theme);
+    let Rgba([r, g, b, _]) = theme.background;
+    let (width, height) = tiles.dimensions();
+    let image = RgbaImage::from_fn(width, height, |x, y| {
+        let [r, g, b] =
+            color(tiles.get(x, y).unwrap_or_default(), theme, walls).unwrap_or([r, g, b]);
+        Rgba([r, g, b, 255])
+    });
+    imageops::resize(
+        &image,
+        width * scale,
+        height * scale,
+        imageops::FilterType::Nearest,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_lines, to_png_image};
+    use crate::ui::renderer::Renderer;
+    use asterion_core::{Theme, Tile, TileMap};
+
+    #[test]
+    fn test_snapshot() {
+        let theme = Theme::dark();
+        let mut tiles = TileMap::new(1, 6, 4);
+        for x in 0..6 {
+            tiles.set(x, 0, Tile::Wall { visibility: 200 });
+            tiles.set(x, 3, Tile::Wall { visibility: 200 });
+        }
+        tiles.set(0, 1, Tile::Entrance { visibility: 255 });
+        tiles.set(1, 1, Tile::Floor { visibility: 255 });
+        tiles.set(2, 1, Tile::Hero);
+        tiles.set(3, 1, Tile::Floor { visibility: 100 });
+        tiles.set(4, 1, Tile::ChasingMinotaur);
+        tiles.set(5, 1, Tile::FoggedFloor);
+        tiles.set(3, 2, Tile::Floor { visibility: 100 });
+        tiles.set(3, 3, Tile::Floor { visibility: 100 });
+
+        let snapshot = |renderer: Renderer| {
+            to_lines(&tiles, &theme, renderer)
+                .iter()
+                .map(|line| {
+                    line.spans
+                        .iter()
+                        .map(|span| span.content.as_ref())
+                        .collect::<String>()
+                })
+                .collect::<Vec<String>>()
+        };
+
+        // Dots need the rest of their character to be empty.
+        assert_eq!(snapshot(Renderer::HalfBlock), ["←1▀▀▀▀", "▄▄▄·▄▄"]);
+        // Characters holding walls, a hero or minotaur and the floor split them
+        // in two colors, the brighter pixels going with the walls.
+        assert_eq!(snapshot(Renderer::Quadrant), ["←▛▛", "▄▖▄"]);
+
+        let png = to_png_image(&tiles, &theme, 4);
+        assert_eq!(png.dimensions(), (24, 16));
+        assert_eq!(png.get_pixel(9, 5).0, [35, 35, 255, 255]);
+    }
+}
@@ -1,20 +1,36 @@
-use super::utils::{img_to_lines, RataColor};
-use crate::{constants::UI_SCREEN_SIZE, AppResult, PlayerId};
-use asterion_core::{Entity, Game, GameColors, Hero, Maze, MAX_MAZE_ID, POWER_UPS_PER_ROOM};
+use super::renderer::Renderer;
+use super::tiles;
+use super::utils::RataColor;
+use crate::{
+    constants::{MIN_UI_SCREEN_SIZE, UI_SCREEN_SIZE},
+    feed::{AnnouncementKind, Announcements},
+    AppResult, PlayerId,
+};
 use anyhow::anyhow;
+use asterion_core::{
+    Camera, DeathPolicy, Entity, Game, Hero, Theme, MAX_MAZE_ID, POWER_UPS_PER_ROOM,
+};
 use itertools::Itertools;
 use ratatui::{
-    layout::{Constraint, Layout, Margin, Rect},
+    layout::{Constraint, Layout, Margin, Position, Rect},
     style::{Color, Style, Styled},
     symbols::border,
     text::{Line, Span},
-    widgets::{Block, BorderType, Paragraph, Wrap},
+    widgets::{Block, BorderType, Clear, Paragraph, Wrap},
     Frame,
 };
 use std::time::{Duration, Instant};
 
 const MINORADAR: [&str; 8] = ["▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
 const NAME_LENGTH: usize = 13;
+const MAX_HEADER_SPLITS: usize = 4;
+const POPUP_WIDTH: u16 = 40;
+const MAX_FEED_LINES: usize = 8;
+const MAX_NPC_LINES: usize = 4;
+const SIDEBAR_WIDTH: u16 = 24;
+const HEADER_HEIGHT: u16 = 6;
+const COMPACT_HEADER_HEIGHT: u16 = 2;
+const MINIMAP_SIZE: (u16, u16) = (26, 10);
 
 const TITLE: [&str; 29] = [
     "     ██▓    ▄▄▄          ▄████▄   ▄▄▄        ██████  ▄▄▄            ",
@@ -81,7 +97,7 @@ fn title_paragraph<'a>() -> Paragraph<'a> {
     Paragraph::new(lines).centered()
 }
 
-fn format_duration(duration: &Duration) -> String {
+pub(crate) fn format_duration(duration: &Duration) -> String {
     let seconds = duration.as_secs() % 60;
     let minutes = (duration.as_secs() / 60) % 60;
     let hours = (duration.as_secs() / 60) / 60;
@@ -95,11 +111,112 @@ fn format_duration(duration: &Duration) -> String {
     formatted_duration.to_string()
 }
 
-fn render_header(frame: &mut Frame, game: &Game, hero: &Hero, area: Rect) -> AppResult<()> {
+fn format_split(duration: &Duration) -> String {
+    let tenths = duration.subsec_millis() / 100;
+    let seconds = duration.as_secs() % 60;
+    let minutes = duration.as_secs() / 60;
+    format!("{minutes}:{seconds:02}.{tenths}")
+}
+
+/// Difference between a split and the personal best one, and whether the
+/// split is ahead of it.
+fn split_delta(split: &Duration, best: Option<&Duration>) -> Option<(String, bool)> {
+    let best = best?;
+    if split <= best {
+        Some((format!(" -{:.1}", (*best - *split).as_secs_f64()), true))
+    } else {
+        Some((format!(" +{:.1}", (*split - *best).as_secs_f64()), false))
+    }
+}
+
+fn split_delta_span<'a>(split: &Duration, best: Option<&Duration>, theme: &Theme) -> Span<'a> {
+    match split_delta(split, best) {
+        Some((delta, true)) => Span::styled(delta, theme.ahead.to_color()),
+        Some((delta, false)) => Span::styled(delta, theme.behind.to_color()),
+        None => Span::raw(""),
+    }
+}
+
+fn splits_line<'a>(hero: &Hero, theme: &Theme) -> Line<'a> {
+    let splits = &hero.stats().splits;
+    let best_splits = hero.best_splits();
+
+    let mut spans = vec![Span::raw("Splits")];
+    for (room, split) in splits
+        .iter()
+        .enumerate()
+        .skip(splits.len().saturating_sub(MAX_HEADER_SPLITS))
+    {
+        spans.push(Span::raw(format!(" r{} {}", room + 1, format_split(split))));
+        spans.push(split_delta_span(split, best_splits.get(room), theme));
+        spans.push(Span::raw(" |"));
+    }
+
+    if hero.has_won().is_none() && !hero.is_dead() {
+        let elapsed = hero.elapsed_duration_from_start();
+        spans.push(Span::raw(format!(
+            " r{} {}",
+            hero.maze_id() + 1,
+            format_split(&elapsed)
+        )));
+        spans.push(split_delta_span(
+            &elapsed,
+            best_splits.get(hero.maze_id()),
+            theme,
+        ));
+    }
+
+    Line::from(spans)
+}
+
+fn run_breakdown_lines<'a>(hero: &Hero) -> Vec<Line<'a>> {
+    let stats = hero.stats();
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(format!(
+            "Steps {} - Power ups {} - Near misses {}",
+            stats.steps, stats.power_ups, stats.near_misses
+        )),
+    ];
+
+    let deaths = hero
+        .deaths_by_room()
+        .iter()
+        .enumerate()
+        .filter(|(_, &deaths)| deaths > 0)
+        .map(|(room, deaths)| format!("r{} {}", room + 1, deaths))
+        .join(" ");
+    if !deaths.is_empty() {
+        lines.push(Line::from(format!("Deaths {deaths}")));
+    }
+
+    for (room, split) in stats.splits.iter().enumerate() {
+        let delta = split_delta(split, hero.best_splits().get(room))
+            .map(|(delta, _)| delta)
+            .unwrap_or_default();
+        lines.push(Line::from(format!(
+            "r{} {}{}",
+            room + 1,
+            format_split(split),
+            delta
+        )));
+    }
+
+    lines
+}
+
+fn render_header(
+    frame: &mut Frame,
+    game: &Game,
+    hero: &Hero,
+    theme: &Theme,
+    compact: bool,
+    area: Rect,
+) -> AppResult<()> {
     let number_of_players = game.number_of_players();
     let maze = game.get_maze(hero.maze_id());
 
-    let mut lines = vec![Line::from(format!(
+    let mut line = vec![Span::raw(format!(
         "There {} {} hero{} in the labyrinth... - Game time {}",
         if number_of_players == 1 { "is" } else { "are" },
         number_of_players,
@@ -107,18 +224,53 @@ fn render_header(frame: &mut Frame, game: &Game, hero: &Hero, area: Rect) -> App
         format_duration(&hero.elapsed_duration_from_start()),
     ))];
 
+    if game.config().time_trial {
+        line.push(Span::raw(" - Daily time trial - "));
+        line.push(Span::styled(
+            format!("Ghost {}", hero.ghost_target()),
+            theme.ghost.to_color(),
+        ));
+        if let Some(run) = game.ghost_run(hero) {
+            line.push(Span::raw(format!(
+                " ({}, {})",
+                run.name,
+                format_duration(&run.duration)
+            )));
+        }
+    }
+    let mut lines = vec![Line::from(line)];
+
     let num_minotaurs = game.minotaurs_in_maze(hero.maze_id());
     let (alarm_level, min_distance_squared) = game.alarm_level(&hero.id());
     let radar_power = 16 * 16 / min_distance_squared.max(1);
     let minoradar: String = MINORADAR.iter().take(radar_power).copied().collect();
     let collected = hero.power_ups_collected_in_maze(maze.id());
 
-    lines.push(Line::from(vec![
-        Span::styled(format!("{}  ", hero.name()), GameColors::HERO.to_color()),
+    let mut line = vec![
+        Span::styled(format!("{}  ", hero.name()), theme.hero.to_color()),
         Span::raw(format!("Vision {} ", hero.vision())),
         Span::raw(format!("Speed {} ", hero.speed())),
         Span::raw(format!("Memory {} ", hero.memory())),
-    ]));
+    ];
+
+    match game.config().death_policy {
+        DeathPolicy::Lives { lives } => line.push(Span::raw(format!(
+            "- Lives {}",
+            lives.saturating_sub(hero.stats().deaths)
+        ))),
+        DeathPolicy::Checkpoints => {
+            if let Some((maze_id, _)) = hero.checkpoint() {
+                line.push(Span::styled(
+                    format!("- Checkpoint r{}", maze_id + 1),
+                    theme.shrine.to_color(),
+                ))
+            } else {
+                line.push(Span::raw("- No checkpoint"))
+            }
+        }
+        DeathPolicy::Restart | DeathPolicy::LosePowerUp => {}
+    }
+    lines.push(Line::from(line));
 
     let mut line = vec![
         Span::raw(format!(
@@ -136,7 +288,7 @@ fn render_header(frame: &mut Frame, game: &Game, hero: &Hero, area: Rect) -> App
         )),
         Span::styled(
             format!("{minoradar:8} "),
-            Style::new().fg(alarm_level.rgba().to_color()),
+            Style::new().fg(alarm_level.rgba(theme).to_color()),
         ),
     ];
 
@@ -147,75 +299,197 @@ fn render_header(frame: &mut Frame, game: &Game, hero: &Hero, area: Rect) -> App
         )))
     }
     lines.push(Line::from(line));
+    lines.push(splits_line(hero, theme));
 
-    frame.render_widget(
-        Paragraph::new(lines).block(Block::bordered().border_type(BorderType::Double)),
-        area,
-    );
+    if compact {
+        // Keep the hero and room lines only, without borders.
+        frame.render_widget(Paragraph::new(lines.drain(1..3).collect_vec()), area);
+    } else {
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().border_type(BorderType::Double)),
+            area,
+        );
+    }
 
     Ok(())
 }
 
-fn render_sidebar(frame: &mut Frame, game: &Game, hero: &Hero, area: Rect) -> AppResult<()> {
+/// Greedily wraps `text` on spaces into lines at most `width` chars long.
+fn wrap_words(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for word in text.split(' ') {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+    lines
+}
+
+fn render_sidebar(
+    frame: &mut Frame,
+    game: &Game,
+    hero: &Hero,
+    theme: &Theme,
+    renderer: Renderer,
+    announcements: &Announcements,
+    area: Rect,
+) -> AppResult<()> {
+    let feed_lines = if hero.announcements_muted() {
+        vec![]
+    } else {
+        announcements
+            .entries()
+            .flat_map(|(kind, text)| {
+                let color = match kind {
+                    AnnouncementKind::Death => theme.chasing_minotaur,
+                    AnnouncementKind::Record => theme.ghost,
+                    AnnouncementKind::Victory => theme.power_up,
+                };
+                wrap_words(text, area.width.saturating_sub(2) as usize)
+                    .into_iter()
+                    .map(move |line| Line::styled(line, color.to_color()))
+            })
+            .take(MAX_FEED_LINES)
+            .collect_vec()
+    };
+    let feed_height = if feed_lines.is_empty() {
+        0
+    } else {
+        feed_lines.len() as u16 + 2
+    };
+
+    let npc_lines = game
+        .top_heros()
+        .iter()
+        .filter(|(id, ..)| game.is_npc(id))
+        .take(MAX_NPC_LINES)
+        .map(|(_, name, maze_id, _, _)| {
+            Line::styled(
+                format!("{name:<NAME_LENGTH$} r{}", maze_id + 1),
+                theme.other_hero.to_color(),
+            )
+        })
+        .collect_vec();
+    let npc_height = if npc_lines.is_empty() {
+        0
+    } else {
+        npc_lines.len() as u16 + 2
+    };
+
     let split = Layout::vertical([
         Constraint::Min(15),
+        Constraint::Length(feed_height),
         Constraint::Max(12),
+        Constraint::Length(npc_height),
         Constraint::Max(12),
     ])
     .split(area);
 
-    let lines = vec![
+    let mut lines = vec![
         Line::from("←↑→↓: move"),
         Line::from("'a'/'d': rotate"),
+        Line::from("'m': mute news"),
+        Line::from(format!("'w': theme {}", theme.name)),
+        Line::from("'c': cycle colors"),
+        Line::from(format!("'r': {renderer}")),
         Line::from("Esc: quit"),
+    ];
+
+    if game.config().time_trial {
+        lines.push(Line::from("'g': cycle ghost"));
+    }
+
+    lines.append(&mut vec![
         Line::from(""),
         Line::from(vec![
-            Span::styled("██", GameColors::HERO.to_color()),
+            Span::styled("██", theme.hero.to_color()),
             Span::raw(format!(" {:12}", "Hero")),
         ]),
         Line::from(vec![
-            Span::styled("██", GameColors::OTHER_HERO.to_color()),
+            Span::styled("██", theme.other_hero.to_color()),
             Span::raw(format!(" {:12}", "Other heros")),
         ]),
         Line::from(vec![
-            Span::styled("██", GameColors::MINOTAUR.to_color()),
+            Span::styled("██", theme.minotaur.to_color()),
             Span::raw(format!(" {:12}", "Minotaur")),
         ]),
         Line::from(vec![
-            Span::styled("██", GameColors::CHASING_MINOTAUR.to_color()),
+            Span::styled("██", theme.chasing_minotaur.to_color()),
             Span::raw(format!(" {:12}", "Minotaur (run!)")),
         ]),
         Line::from(vec![
-            Span::styled("██", GameColors::POWER_UP.to_color()),
+            Span::styled("██", theme.power_up.to_color()),
             Span::raw(format!(" {:12}", "Power up")),
         ]),
-        Line::from(""),
-        Line::from("Run from the minotaurs".to_string()),
-        Line::from("and try to get as far".to_string()),
-        Line::from("as possible.".to_string()),
-    ];
+    ]);
+
+    if game.config().death_policy == DeathPolicy::Checkpoints {
+        lines.push(Line::from(vec![
+            Span::styled("██", theme.shrine.to_color()),
+            Span::raw(format!(" {:12}", "Shrine")),
+        ]));
+    }
+
+    if game.config().time_trial {
+        lines.append(&mut vec![
+            Line::from(vec![
+                Span::styled("██", theme.ghost.to_color()),
+                Span::raw(format!(" {:12}", "Ghost")),
+            ]),
+            Line::from(""),
+            Line::from("Race your ghost out!".to_string()),
+        ]);
+    } else {
+        lines.append(&mut vec![
+            Line::from(""),
+            Line::from("Run from the minotaurs".to_string()),
+            Line::from("and try to get as far".to_string()),
+            Line::from("as possible.".to_string()),
+        ]);
+    }
 
     frame.render_widget(
         Paragraph::new(lines).block(Block::bordered().border_set(border::DOUBLE)),
         split[0],
     );
+
+    if !feed_lines.is_empty() {
+        frame.render_widget(
+            Paragraph::new(feed_lines)
+                .block(Block::bordered().title("News").border_set(border::DOUBLE)),
+            split[1],
+        );
+    }
+
+    // Deathless runs are only worth marking when dying does not restart the run.
+    let mark_deathless = game.config().death_policy != DeathPolicy::Restart;
     let lines = game
         .top_heros()
         .iter()
+        .filter(|(id, ..)| !game.is_npc(id))
         .take(10)
-        .map(|(id, name, maze_id, duration)| {
+        .map(|(id, name, maze_id, duration, deathless)| {
             let record = if *maze_id < MAX_MAZE_ID {
                 format!("r{}", maze_id + 1,)
             } else {
                 format_duration(duration)
             };
+            let mark = if mark_deathless && *deathless {
+                "*"
+            } else {
+                ""
+            };
             Line::from(Span::styled(
-                format!("{name:<NAME_LENGTH$} {record}"),
+                format!("{name:<NAME_LENGTH$} {record}{mark}"),
                 if game.get_hero(id).is_some() {
                     if *id == hero.id() {
-                        Style::new().fg(GameColors::HERO.to_color())
+                        Style::new().fg(theme.hero.to_color())
                     } else {
-                        Style::new().fg(GameColors::OTHER_HERO.to_color())
+                        Style::new().fg(theme.other_hero.to_color())
                     }
                 } else {
                     Style::new()
@@ -227,12 +501,24 @@ fn render_sidebar(frame: &mut Frame, game: &Game, hero: &Hero, area: Rect) -> Ap
     frame.render_widget(
         Paragraph::new(lines).block(
             Block::bordered()
-                .title("Top Heros")
+                .title(if mark_deathless {
+                    "Top Heros *deathless"
+                } else {
+                    "Top Heros"
+                })
                 .border_set(border::DOUBLE),
         ),
-        split[1],
+        split[2],
     );
 
+    if !npc_lines.is_empty() {
+        frame.render_widget(
+            Paragraph::new(npc_lines)
+                .block(Block::bordered().title("Npcs").border_set(border::DOUBLE)),
+            split[3],
+        );
+    }
+
     let lines = game
         .top_minotaurs()
         .iter()
@@ -253,17 +539,22 @@ fn render_sidebar(frame: &mut Frame, game: &Game, hero: &Hero, area: Rect) -> Ap
                 .title("Top Minotaurs")
                 .border_set(border::DOUBLE),
         ),
-        split[2],
+        split[4],
     );
 
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn render(
     frame: &mut Frame,
     game: &Game,
     player_id: PlayerId,
     start_instant: Instant,
+    announcements: &Announcements,
+    camera: &mut Camera,
+    renderer: Renderer,
+    sidebar_overlay: bool,
 ) -> AppResult<()> {
     let screen_area = screen_area(frame.area());
 
@@ -272,14 +563,14 @@ pub fn render(
         return Ok(());
     }
 
-    if screen_area.width < UI_SCREEN_SIZE.0 || screen_area.height < UI_SCREEN_SIZE.1 {
+    if screen_area.width < MIN_UI_SCREEN_SIZE.0 || screen_area.height < MIN_UI_SCREEN_SIZE.1 {
         frame.render_widget(
             Paragraph::new(format!(
                 " Frame size {}x{} is smaller than the minimum size {}x{}.\nPlease resize it or exit with 'q'.",
                 screen_area.width,
                 screen_area.height,
-                UI_SCREEN_SIZE.0,
-                UI_SCREEN_SIZE.1
+                MIN_UI_SCREEN_SIZE.0,
+                MIN_UI_SCREEN_SIZE.1
             ))
             .centered()
             .wrap(Wrap { trim: true }),
@@ -294,74 +585,197 @@ pub fn render(
         return Err(anyhow!("Missing hero {player_id}"));
     };
 
-    let h_split =
-        Layout::horizontal([Constraint::Fill(1), Constraint::Length(24)]).split(screen_area);
-    render_sidebar(frame, game, hero, h_split[1])?;
-
-    let v_split = Layout::vertical([Constraint::Length(6), Constraint::Fill(1)]).split(h_split[0]);
-    render_header(frame, game, hero, v_split[0])?;
+    let theme = game.theme(hero.ui_options());
+    clear(frame, frame.area(), theme);
+
+    // Narrow terminals get the sidebar as an overlay toggled with Tab instead.
+    let docked_sidebar = screen_area.width >= UI_SCREEN_SIZE.0;
+    let main_area = if docked_sidebar {
+        let h_split = Layout::horizontal([Constraint::Fill(1), Constraint::Length(SIDEBAR_WIDTH)])
+            .split(screen_area);
+        render_sidebar(
+            frame,
+            game,
+            hero,
+            theme,
+            renderer,
+            announcements,
+            h_split[1],
+        )?;
+        h_split[0]
+    } else {
+        screen_area
+    };
 
-    let image = game.draw(player_id)?;
+    let compact_header = screen_area.height < UI_SCREEN_SIZE.1;
+    let header_height = if compact_header {
+        COMPACT_HEADER_HEIGHT
+    } else {
+        HEADER_HEIGHT
+    };
+    let v_split =
+        Layout::vertical([Constraint::Length(header_height), Constraint::Fill(1)]).split(main_area);
+    render_header(frame, game, hero, theme, compact_header, v_split[0])?;
+
+    // The camera scrolls the room with the hero when it does not fit, in
+    // steps of the pixels held by a character.
+    let maze_area = v_split[1].inner(Margin::new(1, 1));
+    let bounds = game.get_maze(hero.maze_id()).dimensions();
+    let (cell_width, cell_height) = renderer.cell_size();
+    camera.set_cell_size(cell_width, cell_height);
+    camera.resize(
+        cell_width * maze_area.width as u32,
+        cell_height * maze_area.height as u32,
+    );
+    camera.follow(hero.maze_id(), hero.position(), bounds);
 
-    // Override empty positions.
-    let override_positions = game.image_char_overrides(player_id, &image)?;
+    let tiles = game.tiles(player_id, camera)?;
 
+    let mut block = Block::bordered().border_type(BorderType::Double);
+    if !docked_sidebar {
+        block = block.title(" Tab: sidebar ");
+    }
     frame.render_widget(
-        Paragraph::new(img_to_lines(
-            &image,
-            override_positions,
-            Maze::background_color(),
-        ))
-        .block(Block::bordered().border_type(BorderType::Double)),
+        Paragraph::new(tiles::to_lines(&tiles, theme, renderer)).block(block),
         v_split[1],
     );
 
-    if hero.is_dead() {
-        let width = 32;
-        let height = 6;
-        let popup = Rect::new(
-            v_split[1].x + (v_split[1].width.saturating_sub(width)) / 2,
-            v_split[1].y + (v_split[1].height.saturating_sub(height)) / 2,
+    if camera.view_size(bounds) != bounds {
+        let hero_cell = camera.to_view(hero.position(), bounds).map(|(x, y)| {
+            Position::new(
+                maze_area.x + (x / cell_width) as u16,
+                maze_area.y + (y / cell_height) as u16,
+            )
+        });
+        render_minimap(
+            frame, game, player_id, theme, renderer, maze_area, hero_cell,
+        )?;
+    }
+
+    if !docked_sidebar && sidebar_overlay {
+        let width = SIDEBAR_WIDTH.min(screen_area.width);
+        let area = Rect::new(
+            screen_area.right() - width,
+            screen_area.y,
             width,
-            height,
+            screen_area.height,
         );
+        clear(frame, area, theme);
+        render_sidebar(frame, game, hero, theme, renderer, announcements, area)?;
+    }
+
+    if hero.is_dead() {
+        let mut lines = vec![
+            Line::from(hero.name().to_string()),
+            Line::from(format!("died while exploring room {}", hero.maze_id() + 1)),
+        ];
+        lines.append(&mut run_breakdown_lines(hero));
 
         frame.render_widget(
-            Paragraph::new(vec![
-                Line::from(hero.name().to_string()),
-                Line::from(format!("died while exploring room {}", hero.maze_id() + 1)),
-            ])
-            .centered()
-            .set_style(Style::default().fg(Color::Black).bg(Color::Red))
-            .block(Block::bordered().border_type(BorderType::QuadrantOutside)),
-            popup,
+            Paragraph::new(lines)
+                .centered()
+                .set_style(Style::default().fg(Color::Black).bg(Color::Red))
+                .block(Block::bordered().border_type(BorderType::QuadrantOutside)),
+            popup_area(v_split[1], hero),
         );
     } else if let Some(duration) = hero.has_won().as_ref() {
-        let width = 32;
-        let height = 6;
-        let popup = Rect::new(
-            v_split[1].x + (v_split[1].width.saturating_sub(width)) / 2,
-            v_split[1].y + (v_split[1].height.saturating_sub(height)) / 2,
-            width,
-            height,
-        );
+        let mut lines = vec![
+            Line::from(hero.name().to_string()),
+            Line::from("exited the labyrinth in".to_string()),
+            Line::from(format_duration(duration)),
+        ];
+        lines.append(&mut run_breakdown_lines(hero));
 
         frame.render_widget(
-            Paragraph::new(vec![
-                Line::from(hero.name().to_string()),
-                Line::from("exited the labyrinth in".to_string()),
-                Line::from(format_duration(duration)),
-            ])
-            .centered()
-            .set_style(Style::default().fg(Color::Black).bg(Color::LightGreen))
-            .block(Block::bordered().border_type(BorderType::QuadrantOutside)),
-            popup,
+            Paragraph::new(lines)
+                .centered()
+                .set_style(Style::default().fg(Color::Black).bg(Color::LightGreen))
+                .block(Block::bordered().border_type(BorderType::QuadrantOutside)),
+            popup_area(v_split[1], hero),
         );
     }
 
     Ok(())
 }
 
+/// The top right corner of the room, or the first other corner clear of the
+/// hero when it stands under it.
+fn minimap_area(area: Rect, hero_cell: Option<Position>) -> Rect {
+    let width = MINIMAP_SIZE.0.min(area.width);
+    let height = MINIMAP_SIZE.1.min(area.height);
+    let (left, top) = (area.x, area.y);
+    let (right, bottom) = (area.right() - width, area.bottom() - height);
+    [(right, top), (right, bottom), (left, top), (left, bottom)]
+        .into_iter()
+        .map(|(x, y)| Rect::new(x, y, width, height))
+        .find(|corner| hero_cell.is_none_or(|cell| !corner.contains(cell)))
+        .unwrap_or(Rect::new(right, top, width, height))
+}
+
+fn render_minimap(
+    frame: &mut Frame,
+    game: &Game,
+    player_id: PlayerId,
+    theme: &Theme,
+    renderer: Renderer,
+    area: Rect,
+    hero_cell: Option<Position>,
+) -> AppResult<()> {
+    let area = minimap_area(area, hero_cell);
+    let inner = area.inner(Margin::new(1, 1));
+    let (cell_width, cell_height) = renderer.cell_size();
+    let minimap = game.draw_minimap(
+        player_id,
+        cell_width * inner.width as u32,
+        cell_height * inner.height as u32,
+    )?;
+
+    let title = Line::from(vec![
+        Span::raw("Map "),
+        Span::styled("▀", theme.entrance.to_color()),
+        Span::raw("in "),
+        Span::styled("▀", theme.exit.to_color()),
+        Span::raw("out"),
+    ]);
+
+    clear(frame, area, theme);
+    frame.render_widget(
+        Paragraph::new(tiles::grid_to_lines(&minimap, theme, renderer)).block(
+            Block::bordered()
+                .title(title)
+                .border_type(BorderType::Double),
+        ),
+        area,
+    );
+
+    Ok(())
+}
+
+/// Blanks `area` with the colors of the theme.
+fn clear(frame: &mut Frame, area: Rect, theme: &Theme) {
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Block::new().style(
+            Style::new()
+                .fg(theme.text.to_color())
+                .bg(theme.background.to_color()),
+        ),
+        area,
+    );
+}
+
+fn popup_area(area: Rect, hero: &Hero) -> Rect {
+    // Title lines, blank line, stats and deaths lines, one line per split and borders.
+    let height = (8 + hero.stats().splits.len() as u16).min(area.height);
+    let width = POPUP_WIDTH.min(area.width);
+    Rect::new(
+        area.x + (area.width.saturating_sub(width)) / 2,
+        area.y + (area.height.saturating_sub(height)) / 2,
+        width,
+        height,
+    )
+}
+
 fn screen_area(frame_area: Rect) -> Rect {
     // If area is bigger than UI_SCREEN_SIZE, use a centered rect of the correct size.
     let frame_width = frame_area.width;
@@ -374,3 +788,26 @@ fn screen_area(frame_area: Rect) -> Rect {
         target_height.min(frame_height),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{minimap_area, MINIMAP_SIZE};
+    use ratatui::layout::{Position, Rect};
+
+    #[test]
+    fn test_minimap_area() {
+        let area = Rect::new(2, 3, 80, 30);
+        let top_right = minimap_area(area, None);
+        assert_eq!(top_right.right(), area.right());
+        assert_eq!(top_right.y, area.y);
+        assert_eq!((top_right.width, top_right.height), MINIMAP_SIZE);
+
+        // The minimap makes way for the hero.
+        let hero = Position::new(area.right() - 1, area.y);
+        let bottom_right = minimap_area(area, Some(hero));
+        assert_eq!(bottom_right.right(), area.right());
+        assert_eq!(bottom_right.bottom(), area.bottom());
+        assert!(!bottom_right.contains(hero));
+        assert_eq!(minimap_area(area, Some(Position::new(2, 3))), top_right);
+    }
+}
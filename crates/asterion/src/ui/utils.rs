@@ -1,93 +1,58 @@
-use image::{Pixel, Rgba, RgbaImage};
+use super::renderer::Renderer;
+use image::{Pixel, Rgba};
 use ratatui::{
     style::{Color, Style},
     text::{Line, Span},
 };
-use std::collections::HashMap;
 
-pub fn img_to_lines<'a>(
-    img: &RgbaImage,
-    image_char_overrides: HashMap<(u32, u32), char>,
-    background_color: Rgba<u8>,
+/// Lines of characters drawing a `width`x`height` grid of pixels, each
+/// character holding the pixels of a cell of the renderer. `pixel` gives the
+/// color of a pixel, `None` when empty, and `glyph` a char to draw in place
+/// of the pixels of a character, if any.
+pub fn cells_to_lines<'a>(
+    (width, height): (u32, u32),
+    pixel: impl Fn(u32, u32) -> Option<[u8; 3]>,
+    glyph: impl Fn(&[(u32, u32)]) -> Option<(char, Color)>,
+    background: Rgba<u8>,
+    renderer: Renderer,
 ) -> Vec<Line<'a>> {
+    let Rgba([r, g, b, _]) = background;
+    let (cell_width, cell_height) = renderer.cell_size();
     let mut lines: Vec<Line> = vec![];
-    let width = img.width();
-    let height = img.height();
 
-    for y in (0..height - 1).step_by(2) {
+    // A last row or column of characters can be partly out of the grid.
+    for y in (0..height).step_by(cell_height as usize) {
         let mut line: Vec<Span> = vec![];
 
-        'pixels: for x in 0..width {
-            let &top_pixel = img.get_pixel(x, y);
-            let &btm_pixel = img.get_pixel(x, y + 1);
+        for x in (0..width).step_by(cell_width as usize) {
+            let positions = (0..cell_height)
+                .flat_map(|dy| (0..cell_width).map(move |dx| (x + dx, y + dy)))
+                .collect::<Vec<(u32, u32)>>();
+            let in_grid = positions
+                .iter()
+                .copied()
+                .filter(|&(px, py)| px < width && py < height)
+                .collect::<Vec<(u32, u32)>>();
 
-            // check for overrides.
-            // Maze valid, visible, not occupied positions (alpha < 255)
-            if image_char_overrides.contains_key(&(x, y))
-                && image_char_overrides.contains_key(&(x, y + 1))
-            {
-                let [_, _, _, ta] = top_pixel.0;
-                let [_, _, _, ba] = btm_pixel.0;
-                // This is a hack to only override empty cells,
-                // since 'stuff' is always printed with alpha=255.
-                if ta < 255 && ba < 255 {
-                    let override_char = image_char_overrides.get(&(x, y)).unwrap();
-                    line.push(Span::styled(
-                        override_char.to_string(),
-                        Style::default().fg(Color::Rgb(ta, ta, ta)),
-                    ));
-                    continue 'pixels;
-                }
-            }
-
-            // both pixels are transparent
-            if top_pixel.is_transparent(background_color)
-                && btm_pixel.is_transparent(background_color)
-            {
-                line.push(Span::raw(" "));
+            if let Some((c, color)) = glyph(&in_grid) {
+                line.push(Span::styled(c.to_string(), Style::default().fg(color)));
                 continue;
             }
 
-            // render top pixel
-            if !top_pixel.is_transparent(background_color)
-                && btm_pixel.is_transparent(background_color)
-            {
-                let color = top_pixel.to_color();
-                line.push(Span::styled("▀", Style::default().fg(color)));
-                continue;
-            }
+            let pixels = positions
+                .iter()
+                .map(|&(px, py)| (px < width && py < height).then(|| pixel(px, py)).flatten())
+                .collect::<Vec<Option<[u8; 3]>>>();
 
-            // render bottom pixel
-            if top_pixel.is_transparent(background_color)
-                && !btm_pixel.is_transparent(background_color)
-            {
-                let color = btm_pixel.to_color();
-                line.push(Span::styled("▄", Style::default().fg(color)));
-                continue;
+            let (glyph, fg, bg) = renderer.cell(&pixels, [r, g, b]);
+            let mut style = Style::default();
+            if let Some(fg) = fg {
+                style = style.fg(fg);
             }
-
-            // render both pixels
-            let fg_color = top_pixel.to_color();
-            let bg_color = btm_pixel.to_color();
-            line.push(Span::styled(
-                "▀",
-                Style::default().fg(fg_color).bg(bg_color),
-            ));
-        }
-        lines.push(Line::from(line));
-    }
-    // append last line if height is odd
-    if height % 2 == 1 {
-        let mut line: Vec<Span> = vec![];
-        for x in 0..width {
-            let top_pixel = img.get_pixel(x, height - 1);
-            if top_pixel[3] == 0 {
-                line.push(Span::raw(" "));
-                continue;
+            if let Some(bg) = bg {
+                style = style.bg(bg);
             }
-            let [r, g, b, _] = top_pixel.0;
-            let color = Color::Rgb(r, g, b);
-            line.push(Span::styled("▀", Style::default().fg(color)));
+            line.push(Span::styled(glyph.to_string(), style));
         }
         lines.push(Line::from(line));
     }
@@ -97,6 +62,10 @@ pub fn img_to_lines<'a>(
 
 pub trait RataColor {
     fn to_color(&self) -> Color;
+    /// The color blended over `background` by its alpha channel.
+    fn on(&self, background: Self) -> Color;
+    /// Same as [`RataColor::on`], as channels.
+    fn rgb_on(&self, background: Self) -> [u8; 3];
     fn is_transparent(&self, background_color: Self) -> bool;
 }
 
@@ -111,6 +80,20 @@ impl RataColor for Rgba<u8> {
         Color::Rgb(r, g, b)
     }
 
+    fn on(&self, background: Self) -> Color {
+        let [r, g, b] = self.rgb_on(background);
+        Color::Rgb(r, g, b)
+    }
+
+    fn rgb_on(&self, background: Self) -> [u8; 3] {
+        let [r, g, b, a] = self.0;
+        let [br, bg, bb, _] = background.0;
+        let alpha = a as f32 / 255.0;
+        let blend = |c: u8, bc: u8| (c as f32 * alpha + bc as f32 * (1.0 - alpha)) as u8;
+
+        [blend(r, br), blend(g, bg), blend(b, bb)]
+    }
+
     fn is_transparent(&self, background_color: Self) -> bool {
         self[3] == 0 || self.to_rgb() == background_color.to_rgb()
     }
@@ -1,5 +1,9 @@
 use anyhow::anyhow;
+use asterion_core::MAX_USERNAME_LEN;
 use std::path::PathBuf;
+use unicode_width::UnicodeWidthChar;
+
+const DEFAULT_USERNAME: &str = "hero";
 
 pub type AppResult<T> = Result<T, anyhow::Error>;
 
@@ -13,3 +17,40 @@ pub fn store_path(filename: &str) -> AppResult<PathBuf> {
     let path = config_dirs.join(filename);
     Ok(path)
 }
+
+/// Keeps only the characters of an SSH username which are safe to print in a
+/// single terminal cell: no control sequences, whitespace or wide glyphs.
+pub fn sanitize_username(username: &str) -> String {
+    let username: String = username
+        .chars()
+        .filter(|c| !c.is_control() && !c.is_whitespace() && c.width() == Some(1))
+        .take(MAX_USERNAME_LEN)
+        .collect();
+
+    if username.is_empty() {
+        DEFAULT_USERNAME.to_string()
+    } else {
+        username
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sanitize_username, DEFAULT_USERNAME};
+    use asterion_core::MAX_USERNAME_LEN;
+
+    #[test]
+    fn test_sanitize_username() {
+        assert_eq!(sanitize_username("theseus"), "theseus");
+        // Control sequences, whitespace and wide glyphs are dropped.
+        assert_eq!(sanitize_username("the\x1b[2Jseus"), "the[2Jseus");
+        assert_eq!(sanitize_username("ari adne\n\t"), "ariadne");
+        assert_eq!(sanitize_username("迷宫minos🐂"), "minos");
+        // Nothing printable left.
+        assert_eq!(sanitize_username(""), DEFAULT_USERNAME);
+        assert_eq!(sanitize_username("\x07 \u{200b}迷"), DEFAULT_USERNAME);
+
+        let long = "a".repeat(MAX_USERNAME_LEN * 2);
+        assert_eq!(sanitize_username(&long).len(), MAX_USERNAME_LEN);
+    }
+}